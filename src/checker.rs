@@ -0,0 +1,103 @@
+use super::compile_cache::digest;
+use super::configure::{Configure, Language, ProgramSource};
+use super::execute::{
+    compile_steps, fill_placeholders, read_truncated, run_compile_steps, CancelFlag,
+};
+use std::path::PathBuf;
+const CHECKER_CACHE_DIR: &str = "cache/checkers";
+const DEFAULT_CHECKER_COMPILE_TIME_LIMIT: i32 = 60000000;
+//compile program with language into a folder,reusing the folder of an earlier build of the same source
+fn compile_program(program: &ProgramSource, language: &Language) -> Result<PathBuf, String> {
+    let source = std::fs::read(&program.source_file)
+        .map_err(|r| format!("cannot read {}: {}", program.source_file, r))?;
+    let steps = serde_json::to_vec(&compile_steps(language)).unwrap();
+    let folder = PathBuf::from(CHECKER_CACHE_DIR).join(digest([
+        source.as_slice(),
+        language.file_name.as_bytes(),
+        &steps,
+    ]));
+    if folder.exists() {
+        return Ok(folder);
+    }
+    //build in a scratch folder,so an interrupted build is never taken for a cached one
+    let build_folder = folder.with_extension("build");
+    let _ = std::fs::remove_dir_all(&build_folder);
+    std::fs::create_dir_all(&build_folder).map_err(|r| r.to_string())?;
+    let build_folder_name = build_folder.to_str().unwrap();
+    std::fs::write(build_folder.join(&language.file_name), &source).map_err(|r| r.to_string())?;
+    let compile_out_path = build_folder.join("compile.out");
+    let compile_out = std::fs::File::create(&compile_out_path).map_err(|r| r.to_string())?;
+    let compile_time_limit = match language.compile_time_limit {
+        Some(r) => r,
+        None => DEFAULT_CHECKER_COMPILE_TIME_LIMIT,
+    };
+    if !compile_steps(language).is_empty() {
+        let status = run_compile_steps(
+            build_folder_name,
+            language,
+            &compile_out,
+            compile_time_limit,
+            &CancelFlag::default(),
+        )
+        .map_err(|r| format!("cannot compile {}: {}", program.source_file, r))?;
+        if status.timed_out || !status.status.success() {
+            let info = read_truncated(compile_out_path.to_str().unwrap(), 4096).unwrap_or_default();
+            return Err(format!(
+                "cannot compile {}: {}\n{}",
+                program.source_file,
+                if status.timed_out {
+                    "compilation time limit exceeded".to_string()
+                } else {
+                    status.status.to_string()
+                },
+                info
+            ));
+        }
+    }
+    std::fs::rename(&build_folder, &folder).map_err(|r| r.to_string())?;
+    Ok(folder)
+}
+fn program_command(program: &ProgramSource, config: &Configure) -> Result<Vec<String>, String> {
+    let language = config
+        .languages
+        .iter()
+        .find(|i| i.name == program.language)
+        .ok_or(format!(
+            "language {} of {} not found",
+            program.language, program.source_file
+        ))?;
+    let folder = compile_program(program, language)?;
+    let template = match &language.run {
+        Some(r) => r.clone(),
+        None => vec!["%OUTPUT%".to_string()],
+    };
+    let mut vec_command = fill_placeholders(&template, folder.to_str().unwrap(), language);
+    vec_command.extend(program.args.iter().cloned());
+    Ok(vec_command)
+}
+//compile the special judges,interactors and validators given as source,a failure makes the config invalid
+pub fn compile_checkers(config: &mut Configure) -> Result<(), String> {
+    let languages = config.clone();
+    for problem in &mut config.problems {
+        let misc = match &mut problem.misc {
+            Some(r) => r,
+            None => continue,
+        };
+        if let Some(r) = &misc.special_judge_source {
+            let command = program_command(r, &languages)
+                .map_err(|i| format!("special judge of problem {}: {}", problem.id, i))?;
+            misc.special_judge = Some(command);
+        }
+        if let Some(r) = &misc.interactor_source {
+            let command = program_command(r, &languages)
+                .map_err(|i| format!("interactor of problem {}: {}", problem.id, i))?;
+            misc.interactor = Some(command);
+        }
+        if let Some(r) = &misc.validator_source {
+            let command = program_command(r, &languages)
+                .map_err(|i| format!("validator of problem {}: {}", problem.id, i))?;
+            misc.validator = Some(command);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use super::configure::Comparator;
+use super::enumresult::EnumResult;
+use std::collections::HashMap;
+const DEFAULT_FLOAT_EPS: f64 = 1e-6;
+//lines without trailing spaces,trailing empty lines removed
+fn lines(s: &str) -> Vec<&str> {
+    let s = s.trim_end();
+    if s.is_empty() {
+        return Vec::new();
+    }
+    s.split('\n').map(|i| i.trim_end()).collect()
+}
+fn is_float_equal(out: &str, ans: &str, abs_eps: Option<f64>, rel_eps: Option<f64>) -> bool {
+    if out == ans {
+        return true;
+    }
+    let (out, ans) = match (out.parse::<f64>(), ans.parse::<f64>()) {
+        (Ok(i), Ok(j)) => (i, j),
+        _ => return false,
+    };
+    let (abs_eps, rel_eps) = match (abs_eps, rel_eps) {
+        (None, None) => (Some(DEFAULT_FLOAT_EPS), Some(DEFAULT_FLOAT_EPS)),
+        r => r,
+    };
+    let diff = (out - ans).abs();
+    abs_eps.is_some_and(|i| diff <= i) || rel_eps.is_some_and(|i| diff <= i * ans.abs())
+}
+//characters shown from each side of a difference
+const EXCERPT_LEN: usize = 32;
+//a quoted part of s starting a few characters before column(from 1)
+fn excerpt(s: &str, column: usize) -> String {
+    let start = column.saturating_sub(EXCERPT_LEN / 4 + 1);
+    let count = s.chars().count();
+    let mut r: String = s.chars().skip(start).take(EXCERPT_LEN).collect();
+    r = format!("{:?}", r);
+    if start > 0 {
+        r.insert_str(0, "...");
+    }
+    if start + EXCERPT_LEN < count {
+        r.push_str("...");
+    }
+    r
+}
+//first column(from 1) where the two strings differ
+fn first_column(out: &str, ans: &str) -> Option<usize> {
+    if out == ans {
+        return None;
+    }
+    Some(
+        out.chars()
+            .zip(ans.chars())
+            .take_while(|(i, j)| i == j)
+            .count()
+            + 1,
+    )
+}
+fn diff_length(unit: &str, out_len: usize, ans_len: usize) -> Option<String> {
+    if out_len < ans_len {
+        Some(format!(
+            "output too short: expected {} {}, found {}",
+            ans_len, unit, out_len
+        ))
+    } else if out_len > ans_len {
+        Some(format!(
+            "output too long: expected {} {}, found {}",
+            ans_len, unit, out_len
+        ))
+    } else {
+        None
+    }
+}
+fn diff_lines(out: &[&str], ans: &[&str], ignore_case: bool) -> Option<String> {
+    for (index, (i, j)) in out.iter().zip(ans).enumerate() {
+        let column = if ignore_case {
+            first_column(&i.to_lowercase(), &j.to_lowercase())
+        } else {
+            first_column(i, j)
+        };
+        if let Some(column) = column {
+            return Some(format!(
+                "line {}, column {}: expected {}, found {}",
+                index + 1,
+                column,
+                excerpt(j, column),
+                excerpt(i, column)
+            ));
+        }
+    }
+    diff_length("lines", out.len(), ans.len())
+}
+//tokens with the line(from 1) they are on
+fn tokens(s: &str) -> Vec<(usize, &str)> {
+    s.split('\n')
+        .enumerate()
+        .flat_map(|(index, line)| line.split_whitespace().map(move |i| (index + 1, i)))
+        .collect()
+}
+fn diff_tokens(out: &str, ans: &str, is_equal: impl Fn(&str, &str) -> bool) -> Option<String> {
+    let vec_out = tokens(out);
+    let vec_ans = tokens(ans);
+    for (index, ((line, i), (_, j))) in vec_out.iter().zip(&vec_ans).enumerate() {
+        if !is_equal(i, j) {
+            return Some(format!(
+                "line {}, token {}: expected {}, found {}",
+                line,
+                index + 1,
+                excerpt(j, 1),
+                excerpt(i, 1)
+            ));
+        }
+    }
+    diff_length("tokens", vec_out.len(), vec_ans.len())
+}
+fn diff_unordered_lines(out: &[&str], ans: &[&str]) -> Option<String> {
+    if let Some(r) = diff_length("lines", out.len(), ans.len()) {
+        return Some(r);
+    }
+    let mut count: HashMap<&str, i32> = HashMap::new();
+    for i in out {
+        *count.entry(i).or_default() += 1;
+    }
+    for (index, i) in ans.iter().enumerate() {
+        let entry = count.entry(i).or_default();
+        if *entry == 0 {
+            return Some(format!(
+                "line {} of the answer not found in the output: {}",
+                index + 1,
+                excerpt(i, 1)
+            ));
+        }
+        *entry -= 1;
+    }
+    None
+}
+//a wrong output that equals the answer once all whitespace is normalized
+pub fn is_presentation_error(out: &str, ans: &str) -> bool {
+    out.split_whitespace().eq(ans.split_whitespace())
+}
+//where the output first differs from the answer,None if they match
+pub fn compare_output(out: &str, ans: &str, comparator: &Comparator) -> (EnumResult, String) {
+    let difference = match comparator {
+        Comparator::Strict => {
+            if out == ans {
+                None
+            } else {
+                let vec_out: Vec<&str> = out.split('\n').collect();
+                let vec_ans: Vec<&str> = ans.split('\n').collect();
+                diff_lines(&vec_out, &vec_ans, false)
+            }
+        }
+        Comparator::Lines => diff_lines(&lines(out), &lines(ans), false),
+        Comparator::Tokens => diff_tokens(out, ans, |i, j| i == j),
+        Comparator::Float { abs_eps, rel_eps } => {
+            diff_tokens(out, ans, |i, j| is_float_equal(i, j, *abs_eps, *rel_eps))
+        }
+        Comparator::CaseInsensitive => diff_lines(&lines(out), &lines(ans), true),
+        Comparator::UnorderedLines => diff_unordered_lines(&lines(out), &lines(ans)),
+        Comparator::YesNo => diff_tokens(out, ans, |i, j| {
+            (i.eq_ignore_ascii_case("yes") || i.eq_ignore_ascii_case("no"))
+                && i.eq_ignore_ascii_case(j)
+        }),
+    };
+    match difference {
+        None => (EnumResult::Accepted, String::new()),
+        Some(r) => (EnumResult::WrongAnswer, r),
+    }
+}
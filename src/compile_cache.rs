@@ -0,0 +1,136 @@
+use super::configure::Language;
+use sha1::{Digest, Sha1};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+const COMPILE_CACHE_DIR: &str = "cache/submissions";
+const DEFAULT_COMPILE_CACHE_SIZE: u64 = 1 << 30;
+//bytes of compiled submissions kept on disk,0 turns the cache off
+static CACHE_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_COMPILE_CACHE_SIZE);
+pub fn set_cache_size(size: Option<u64>) {
+    CACHE_SIZE.store(size.unwrap_or(DEFAULT_COMPILE_CACHE_SIZE), Ordering::SeqCst);
+}
+//a hex digest of fields that stays the same across builds,as it names folders on disk;
+//each field is prefixed by its length so the boundaries between them count
+pub fn digest<'a>(fields: impl IntoIterator<Item = &'a [u8]>) -> String {
+    let mut hasher = Sha1::new();
+    for i in fields {
+        hasher.update((i.len() as u64).to_le_bytes());
+        hasher.update(i);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|i| format!("{:02x}", i))
+        .collect()
+}
+//identify a compilation by the source and everything of the language that affects it
+pub fn cache_key(source_code: &str, language: &Language) -> String {
+    let settings = serde_json::to_vec(&(
+        &language.command,
+        &language.compile_steps,
+        language.compile_memory_limit,
+    ))
+    .unwrap();
+    digest([
+        source_code.as_bytes(),
+        language.file_name.as_bytes(),
+        &settings,
+    ])
+}
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for i in std::fs::read_dir(from)? {
+        let i = i?;
+        if i.file_type()?.is_dir() {
+            copy_dir(&i.path(), &to.join(i.file_name()))?;
+        } else {
+            std::fs::copy(i.path(), to.join(i.file_name()))?;
+        }
+    }
+    Ok(())
+}
+fn dir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .map(|r| {
+            r.filter_map(|i| i.ok())
+                .map(|i| match i.file_type() {
+                    Ok(t) if t.is_dir() => dir_size(&i.path()),
+                    _ => i.metadata().map(|m| m.len()).unwrap_or(0),
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+//copy what the compilation left in its folder(compile.out and the compiled files) into
+//folder_name,None on a miss
+pub fn load(key: &str, folder_name: &str) -> Option<bool> {
+    if CACHE_SIZE.load(Ordering::SeqCst) == 0 {
+        return None;
+    }
+    let entry = PathBuf::from(COMPILE_CACHE_DIR).join(key);
+    if !entry.exists() {
+        return None;
+    }
+    //the entry may be evicted meanwhile,which counts as a miss
+    copy_dir(&entry.join("files"), Path::new(folder_name)).ok()?;
+    //the modification time of the used file orders the entries for eviction
+    let used = File::create(entry.join("used")).and_then(|r| r.set_modified(SystemTime::now()));
+    used.ok()?;
+    Some(entry.join("success").exists())
+}
+//keep the files of folder_name after a compilation,then evict the least recently used entries
+pub fn store(key: &str, folder_name: &str, is_compiled: bool) {
+    let size = CACHE_SIZE.load(Ordering::SeqCst);
+    if size == 0 {
+        return;
+    }
+    let entry = PathBuf::from(COMPILE_CACHE_DIR).join(key);
+    if entry.join("files").exists() {
+        return;
+    }
+    //fill a scratch folder first,so a half written entry is never loaded
+    let scratch = PathBuf::from(COMPILE_CACHE_DIR).join(format!("{}.{}", key, folder_name));
+    let filled = copy_dir(Path::new(folder_name), &scratch.join("files"))
+        .and_then(|_| File::create(scratch.join("used")).map(|_| ()))
+        .and_then(|_| {
+            if is_compiled {
+                File::create(scratch.join("success"))?;
+            }
+            Ok(())
+        });
+    if filled.is_err() || std::fs::rename(&scratch, &entry).is_err() {
+        let _ = std::fs::remove_dir_all(&scratch);
+        return;
+    }
+    evict(size);
+}
+fn evict(size: u64) {
+    let dir = match std::fs::read_dir(COMPILE_CACHE_DIR) {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    let mut entries: Vec<(SystemTime, PathBuf, u64)> = dir
+        .filter_map(|i| i.ok())
+        .map(|i| i.path())
+        .filter(|i| i.extension().is_none())
+        .map(|i| {
+            let used = std::fs::metadata(i.join("used"))
+                .and_then(|r| r.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let bytes = dir_size(&i);
+            (used, i, bytes)
+        })
+        .collect();
+    let mut total: u64 = entries.iter().map(|i| i.2).sum();
+    entries.sort();
+    for (_, path, bytes) in entries {
+        if total <= size {
+            break;
+        }
+        if std::fs::remove_dir_all(&path).is_ok() {
+            total -= bytes;
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use super::enumresult::EnumResult;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::io::Read;
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -7,25 +8,139 @@ pub enum ProblemType {
     Strict,
     Spj,
     DynamicRanking,
+    Interactive,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ServerInfo {
     pub bind_address: Option<String>,
     pub bind_port: Option<u16>,
+    //number of jobs judged at the same time
+    pub judge_workers: Option<usize>,
+    //check the input files with the validators at startup,problems that fail are not served
+    pub validate_data: Option<bool>,
+    //bytes of compiled submissions kept on disk,0 turns the cache off
+    pub compile_cache_size: Option<u64>,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtaskPolicy {
+    //full score only if every case is accepted
+    AllOrNothing,
+    //score times the lowest case ratio
+    Min,
+    //every case is worth an equal share of the score
+    Sum,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Subtask {
+    pub score: f64,
+    pub policy: Option<SubtaskPolicy>,
+    //case ids,starting from 1
+    pub cases: Vec<i32>,
+    //indexes of earlier subtasks that must get full score,starting from 0
+    pub dependencies: Option<Vec<usize>>,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckerProtocol {
+    //result and info lines on stdout(stderr for interactors)
+    Text,
+    //testlib exit codes,message on stderr
+    Testlib,
+}
+//built-in comparison of the output with the answer
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Comparator {
+    //byte by byte
+    Strict,
+    //line by line,ignoring trailing spaces and trailing empty lines
+    Lines,
+    //whitespace separated tokens
+    Tokens,
+    //tokens,numbers may differ by abs_eps or by rel_eps relative to the answer
+    Float {
+        abs_eps: Option<f64>,
+        rel_eps: Option<f64>,
+    },
+    //lines,ignoring case
+    CaseInsensitive,
+    //lines in any order
+    UnorderedLines,
+    //tokens of yes or no,ignoring case
+    YesNo,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    //in the info of the cases
+    Contestant,
+    //only from /internal/jobs/{id}
+    Admin,
+}
+//a judge program built from source with one of the languages
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProgramSource {
+    pub source_file: String,
+    pub language: String,
+    //arguments of the compiled program,with the same placeholders as special_judge
+    pub args: Vec<String>,
+}
+//a solution of the problem and the result it must get,Accepted for reference solutions
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SolutionCheck {
+    pub source_file: String,
+    pub language: String,
+    pub expected: EnumResult,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Misc {
     pub packing: Option<Vec<Vec<i32>>>,
+    pub subtasks: Option<Vec<Subtask>>,
     pub dynamic_ranking_ratio: Option<f64>,
     pub special_judge: Option<Vec<String>>,
+    pub interactor: Option<Vec<String>>,
+    //compiled at startup,they take the place of special_judge and interactor
+    pub special_judge_source: Option<ProgramSource>,
+    pub interactor_source: Option<ProgramSource>,
+    //exits with 0 if the input file given on stdin(and as %INPUT%) is valid
+    pub validator: Option<Vec<String>>,
+    pub validator_source: Option<ProgramSource>,
+    //judged by /internal/problems/{id}/verify to check the data and limits
+    pub solutions: Option<Vec<SolutionCheck>>,
+    //protocol of special_judge and interactor,text by default
+    pub checker_protocol: Option<CheckerProtocol>,
+    //defaults to strict for strict problems and lines for the others
+    pub comparator: Option<Comparator>,
+    //give Presentation Error instead of Wrong Answer when only whitespace differs
+    pub presentation_error: Option<bool>,
+    //who sees where a wrong output differs from the answer,admin by default
+    pub diagnostics: Option<Visibility>,
+    //cpu time(us) and peak memory(bytes) limits of each special judge run,the memory limit
+    //holds for the interactor too
+    pub spj_time_limit: Option<i32>,
+    pub spj_memory_limit: Option<i32>,
+}
+//commands whose stdout becomes the data of a case,arguments that are files count as part of them
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Generator {
+    pub command: Vec<String>,
+    //reads the generated input on stdin
+    pub solution: Vec<String>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Case {
     pub score: f64,
+    //filled in at startup when the data comes from generator
+    #[serde(default)]
     pub input_file: String,
+    #[serde(default)]
     pub answer_file: String,
+    pub generator: Option<Generator>,
     pub time_limit: i32,
+    pub wall_time_limit: Option<i32>,
     pub memory_limit: i32,
+    pub output_limit: Option<i32>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Problem {
@@ -34,13 +149,50 @@ pub struct Problem {
     #[serde(rename = "type")]
     pub ty: ProblemType,
     pub misc: Option<Misc>,
+    pub output_limit: Option<i32>,
     pub cases: Vec<Case>,
 }
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Sandbox {
+    pub allowed_syscalls: Option<Vec<String>>,
+    //processes and threads the program may run at once,64 by default
+    pub process_limit: Option<u64>,
+}
+//the sandbox of a language is given by its options,or turned on and off by a bool
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SandboxSetting {
+    Enabled(bool),
+    Options(Sandbox),
+}
+fn default_sandbox() -> Option<Sandbox> {
+    Some(Sandbox::default())
+}
+fn deserialize_sandbox<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Sandbox>, D::Error> {
+    Ok(match Option::<SandboxSetting>::deserialize(d)? {
+        Some(SandboxSetting::Enabled(true)) => default_sandbox(),
+        Some(SandboxSetting::Options(r)) => Some(r),
+        Some(SandboxSetting::Enabled(false)) | None => None,
+    })
+}
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Language {
     pub name: String,
     pub file_name: String,
+    //a single compile step,left empty by languages with compile_steps or no compile step at all
+    #[serde(default)]
     pub command: Vec<String>,
+    //run one after another in the folder of the job,with the same placeholders as run
+    pub compile_steps: Option<Vec<Vec<String>>>,
+    //how to run the program,%OUTPUT% by default;%INPUT% is the source file,%OUTPUT% the
+    //compiled program and %FOLDER% the folder holding both
+    pub run: Option<Vec<String>>,
+    pub compile_time_limit: Option<i32>,
+    pub compile_memory_limit: Option<i32>,
+    pub compile_info_limit: Option<i32>,
+    //on unless set to false
+    #[serde(default = "default_sandbox", deserialize_with = "deserialize_sandbox")]
+    pub sandbox: Option<Sandbox>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Configure {
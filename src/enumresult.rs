@@ -4,18 +4,26 @@ pub enum EnumResult {
     Waiting,
     Running,
     Accepted,
+    #[serde(rename = "Partially Accepted")]
+    PartiallyAccepted,
     #[serde(rename = "Compilation Error")]
     CompilationError,
     #[serde(rename = "Compilation Success")]
     CompilationSuccess,
     #[serde(rename = "Wrong Answer")]
     WrongAnswer,
+    #[serde(rename = "Presentation Error")]
+    PresentationError,
     #[serde(rename = "Runtime Error")]
     RuntimeError,
     #[serde(rename = "Time Limit Exceeded")]
     TimeLimitExceeded,
     #[serde(rename = "Memory Limit Exceeded")]
     MemoryLimitExceeded,
+    #[serde(rename = "Idleness Limit Exceeded")]
+    IdlenessLimitExceeded,
+    #[serde(rename = "Output Limit Exceeded")]
+    OutputLimitExceeded,
     #[serde(rename = "System Error")]
     SystemError,
     #[serde(rename = "SPJ Error")]
@@ -26,9 +34,12 @@ impl EnumResult {
     pub fn to_string(&self) -> String {
         match &self {
             Self::Accepted => "Accepted".to_string(),
+            Self::PartiallyAccepted => "Partially Accepted".to_string(),
             Self::CompilationError => "Compilation Error".to_string(),
             Self::CompilationSuccess => "Compilation Success".to_string(),
             Self::MemoryLimitExceeded => "Memory Limit Exceeded".to_string(),
+            Self::IdlenessLimitExceeded => "Idleness Limit Exceeded".to_string(),
+            Self::OutputLimitExceeded => "Output Limit Exceeded".to_string(),
             Self::Running => "Running".to_string(),
             Self::RuntimeError => "Runtime Error".to_string(),
             Self::SPJError => "SPJ Error".to_string(),
@@ -37,6 +48,7 @@ impl EnumResult {
             Self::TimeLimitExceeded => "Time Limit Exceeded".to_string(),
             Self::Waiting => "Waiting".to_string(),
             Self::WrongAnswer => "Wrong Answer".to_string(),
+            Self::PresentationError => "Presentation Error".to_string(),
         }
     }
     pub fn enumresult_from_string(s: String) -> EnumResult {
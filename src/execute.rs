@@ -1,7 +1,13 @@
 extern crate reqwest;
-use super::configure::{Language, Problem, ProblemType};
+use super::compare::{compare_output, is_presentation_error};
+use super::compile_cache;
+use super::configure::{
+    Case, CheckerProtocol, Comparator, Language, Misc, Problem, ProblemType, Visibility,
+};
 use super::enumresult::EnumResult;
 use super::enumresult::State;
+use super::sandbox::{sandbox_command, sandbox_folder};
+use super::subtask::{is_case_needed, judge_order, problem_subtasks, score_job, SubtaskResult};
 use actix_web::web;
 use chrono::prelude::*;
 use log;
@@ -10,11 +16,15 @@ use r2d2_sqlite::SqliteConnectionManager;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use wait4::Wait4;
-use wait_timeout::ChildExt;
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Submit {
     pub source_code: String,
@@ -24,6 +34,25 @@ pub struct Submit {
     pub problem_id: i32,
 }
 
+//a run of source_code on input,judged by nothing but the limits of the problem
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomRun {
+    pub source_code: String,
+    pub language: String,
+    pub problem_id: i32,
+    pub input: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunResult {
+    //Accepted when the program ends normally within the limits
+    pub result: EnumResult,
+    pub time: i32,
+    pub memory: i32,
+    pub stdout: String,
+    pub stderr: String,
+    pub info: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CaseResult {
     pub id: i32,
@@ -31,6 +60,9 @@ pub struct CaseResult {
     pub time: i32,
     pub memory: i32,
     pub info: String,
+    //share of the case score given by a judge program to a Partially Accepted case
+    #[serde(default)]
+    pub score_ratio: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -43,111 +75,620 @@ pub struct Message {
     pub result: EnumResult,
     pub score: f64,
     pub cases: Vec<CaseResult>,
+    pub subtasks: Vec<SubtaskResult>,
+}
+//a Queueing job of submission with every case and subtask Waiting
+pub fn new_message(
+    id: i32,
+    created_time: String,
+    submission: Submit,
+    problem: &Problem,
+) -> Message {
+    let mut vec_cases = Vec::new();
+    for i in 0..problem.cases.len() + 1 {
+        vec_cases.push(CaseResult {
+            id: i as i32,
+            result: EnumResult::Waiting,
+            time: 0,
+            memory: 0,
+            info: String::from(""),
+            score_ratio: 0.0,
+        });
+    }
+    Message {
+        id,
+        created_time: created_time.clone(),
+        updated_time: created_time,
+        submission,
+        state: State::Queueing,
+        result: EnumResult::Waiting,
+        score: 0.0,
+        cases: vec_cases,
+        subtasks: problem_subtasks(problem)
+            .iter()
+            .enumerate()
+            .map(|(i, _)| SubtaskResult {
+                id: i as i32,
+                result: EnumResult::Waiting,
+                score: 0.0,
+            })
+            .collect(),
+    }
+}
+//read a row of the task table(SELECT *)
+pub fn message_from_row(row: &rusqlite::Row) -> rusqlite::Result<Message> {
+    Ok(Message {
+        id: row.get(0)?,
+        created_time: row.get(5)?,
+        updated_time: row.get(8)?,
+        submission: Submit {
+            source_code: row.get(9)?,
+            language: row.get(4)?,
+            user_id: row.get(1)?,
+            contest_id: row.get(2)?,
+            problem_id: row.get(3)?,
+        },
+        state: State::state_from_string(row.get(6)?),
+        result: EnumResult::enumresult_from_string(row.get(7)?),
+        score: row.get(10)?,
+        cases: {
+            let t: String = row.get(11)?;
+            serde_json::from_str(&t).unwrap()
+        },
+        subtasks: {
+            let t: String = row.get(12)?;
+            serde_json::from_str(&t).unwrap()
+        },
+    })
 }
 pub fn match_result(
     out_file: String,
     ans_file: String,
     compare: ProblemType,
-) -> Result<EnumResult, std::io::Error> {
+    comparator: &Option<Comparator>,
+    presentation_error: bool,
+) -> Result<(EnumResult, String), std::io::Error> {
     let mut out_stream = File::open(out_file)?;
     let mut ans_stream = File::open(ans_file)?;
     let mut buffer_out = String::new();
     let mut buffer_ans = String::new();
     out_stream.read_to_string(&mut buffer_out)?;
     ans_stream.read_to_string(&mut buffer_ans)?;
-    match compare {
-        ProblemType::Strict => match buffer_out.cmp(&mut buffer_ans) {
-            std::cmp::Ordering::Equal => {
-                return Ok(EnumResult::Accepted);
+    let comparator = match (comparator, compare) {
+        (Some(r), _) => r.clone(),
+        (None, ProblemType::Strict) => Comparator::Strict,
+        (None, _) => Comparator::Lines,
+    };
+    let (result, difference) = compare_output(&buffer_out, &buffer_ans, &comparator);
+    if let EnumResult::WrongAnswer = result {
+        if presentation_error && is_presentation_error(&buffer_out, &buffer_ans) {
+            return Ok((EnumResult::PresentationError, difference));
+        }
+    }
+    Ok((result, difference))
+}
+//the first line a judge program reports is the result,the second line is info,
+//a Partially Accepted result needs a third line with the score ratio between 0 and 1
+pub fn parse_judge_output(full_string: String) -> (EnumResult, String, f64) {
+    let vec_str: Vec<&str> = full_string.split('\n').collect();
+    if vec_str.len() <= 1 {
+        return (EnumResult::SPJError, String::new(), 0.0);
+    }
+    let mut parse_str = "\"".to_string();
+    parse_str.push_str(vec_str[0]);
+    parse_str.push('\"');
+    let enum_res: Result<EnumResult, serde_json::Error> = serde_json::from_str(&parse_str);
+    match enum_res {
+        Ok(EnumResult::PartiallyAccepted) => {
+            let ratio = vec_str
+                .get(2)
+                .and_then(|i| i.trim().parse::<f64>().ok())
+                .filter(|i| (0.0..=1.0).contains(i));
+            match ratio {
+                Some(r) => (EnumResult::PartiallyAccepted, vec_str[1].to_string(), r),
+                None => (
+                    EnumResult::SPJError,
+                    "Partially Accepted without a valid score ratio".to_string(),
+                    0.0,
+                ),
             }
-            _ => {
-                return Ok(EnumResult::WrongAnswer);
+        }
+        Ok(EnumResult::Accepted) => (EnumResult::Accepted, vec_str[1].to_string(), 1.0),
+        Ok(i) => (i, vec_str[1].to_string(), 0.0),
+        Err(_r) => (EnumResult::SPJError, vec_str[1].to_string(), 0.0),
+    }
+}
+//testlib reports through the exit code:0 AC,1 WA,2 PE,3 FAIL,7 points with "points <ratio>" in the message
+pub fn parse_testlib_result(status: ExitStatus, report: String) -> (EnumResult, String, f64) {
+    let info = report.trim().to_string();
+    match status.code() {
+        Some(0) => (EnumResult::Accepted, info, 1.0),
+        Some(1) => (EnumResult::WrongAnswer, info, 0.0),
+        Some(2) => (EnumResult::PresentationError, info, 0.0),
+        Some(7) => {
+            let ratio = info
+                .strip_prefix("points")
+                .and_then(|i| i.split_whitespace().next())
+                .and_then(|i| i.parse::<f64>().ok())
+                .filter(|i| (0.0..=1.0).contains(i));
+            match ratio {
+                Some(r) => (EnumResult::PartiallyAccepted, info, r),
+                None => (EnumResult::SPJError, info, 0.0),
             }
-        },
-        _ => {
-            buffer_ans = buffer_ans.trim_end().to_string();
-            buffer_out = buffer_out.trim_end().to_string();
-            let vec_ans: Vec<_> = buffer_ans.split('\n').collect();
-            let vec_out: Vec<_> = buffer_out.split('\n').collect();
-            if vec_ans.len() != vec_out.len() {
-                return Ok(EnumResult::WrongAnswer);
+        }
+        _ => (EnumResult::SPJError, info, 0.0),
+    }
+}
+//run the special judge under its own limits,writing its stdout and stderr next to the output
+pub fn match_result_spj(
+    list: Vec<String>,
+    misc: &Misc,
+    out_path: &Path,
+    cancel: &CancelFlag,
+) -> Result<(EnumResult, String, f64), std::io::Error> {
+    let stdout_path = out_path.with_extension("spj.out");
+    let stderr_path = out_path.with_extension("spj.err");
+    let mut command = Command::new(&list[0]);
+    command
+        .args(&list[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(File::create(&stdout_path)?))
+        .stderr(Stdio::from(File::create(&stderr_path)?));
+    let time_limit = match misc.spj_time_limit {
+        Some(r) => r,
+        None => DEFAULT_SPJ_TIME_LIMIT,
+    };
+    set_cpu_limit(&mut command, time_limit);
+    if let Some(r) = misc.spj_memory_limit {
+        set_run_memory_limit(&mut command, r);
+    }
+    let run_status = match command.spawn() {
+        Err(r) => return Ok((EnumResult::SPJError, r.to_string(), 0.0)),
+        Ok(mut child) => wait_with_limit(
+            &mut child,
+            Duration::from_micros(time_limit as u64 * 2 + 1000000),
+            cancel,
+        )?,
+    };
+    let stdout = String::from_utf8_lossy(&std::fs::read(&stdout_path)?).to_string();
+    let stderr = String::from_utf8_lossy(&std::fs::read(&stderr_path)?).to_string();
+    if run_status.timed_out
+        || run_status.time > Duration::from_micros(time_limit as u64)
+        || run_status.status.signal() == Some(libc::SIGXCPU)
+    {
+        return Ok((
+            EnumResult::SPJError,
+            "Special judge time limit exceeded".to_string(),
+            0.0,
+        ));
+    }
+    if let Some(r) = misc.spj_memory_limit {
+        if run_status.memory > r as u64 {
+            return Ok((
+                EnumResult::SPJError,
+                "Special judge memory limit exceeded".to_string(),
+                0.0,
+            ));
+        }
+    }
+    if let Some(CheckerProtocol::Testlib) = misc.checker_protocol {
+        return Ok(parse_testlib_result(run_status.status, stderr));
+    }
+    if !run_status.status.success() {
+        return Ok((EnumResult::SPJError, stderr, 0.0));
+    }
+    Ok(parse_judge_output(stdout))
+}
+//the interactor talks to the submission over stdin/stdout and reports on stderr like a spj
+pub fn match_result_interactor(
+    mut interactor: Child,
+    report_path: &PathBuf,
+    duration: Duration,
+    cancel: &CancelFlag,
+    protocol: &Option<CheckerProtocol>,
+    memory_limit: Option<i32>,
+) -> Result<(EnumResult, String, f64), std::io::Error> {
+    let run_status = wait_with_limit(&mut interactor, duration, cancel)?;
+    let mut report = String::new();
+    File::open(report_path)?.read_to_string(&mut report)?;
+    if run_status.timed_out {
+        return Ok((
+            EnumResult::SPJError,
+            "Interactor time limit exceeded".to_string(),
+            0.0,
+        ));
+    }
+    if let Some(r) = memory_limit {
+        if run_status.memory > r as u64 {
+            return Ok((
+                EnumResult::SPJError,
+                "Interactor memory limit exceeded".to_string(),
+                0.0,
+            ));
+        }
+    }
+    if let Some(CheckerProtocol::Testlib) = protocol {
+        return Ok(parse_testlib_result(run_status.status, report));
+    }
+    if !run_status.status.success() {
+        return Ok((EnumResult::SPJError, report, 0.0));
+    }
+    Ok(parse_judge_output(report))
+}
+const DEFAULT_COMPILE_TIME_LIMIT: i32 = 10000000;
+const DEFAULT_COMPILE_INFO_LIMIT: i32 = 4096;
+const DEFAULT_SPJ_TIME_LIMIT: i32 = 10000000;
+//least memory(bytes) a special judge or interactor may reserve beyond its memory limit
+const MEMORY_HEADROOM: u64 = 64 << 20;
+//bytes of stdout and stderr returned by a custom run
+const RUN_OUTPUT_LIMIT: usize = 65536;
+//numbers the folders of custom runs,which have no job id
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+//set by DELETE /jobs/{id} to stop judging a job
+pub type CancelFlag = Arc<AtomicBool>;
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+//how often the memory of a run under a memory limit is checked
+const MEMORY_POLL_INTERVAL: Duration = Duration::from_millis(10);
+//read a text file,keeping at most limit bytes
+pub fn read_truncated(path: &str, limit: usize) -> Result<String, std::io::Error> {
+    let mut buffer = Vec::new();
+    File::open(path)?.read_to_end(&mut buffer)?;
+    let is_truncated = buffer.len() > limit;
+    buffer.truncate(limit);
+    let mut info = String::from_utf8_lossy(&buffer).to_string();
+    if is_truncated {
+        info.push_str("\n...(truncated)");
+    }
+    Ok(info)
+}
+//how a program that did not succeed ended,for the info of its case
+pub fn describe_exit_status(status: &ExitStatus) -> String {
+    let signal = match status.signal() {
+        Some(r) => r,
+        None => {
+            return match status.code() {
+                Some(r) => format!("exited with code {}", r),
+                None => String::new(),
+            }
+        }
+    };
+    let name = match signal {
+        libc::SIGSEGV => {
+            "SIGSEGV (segmentation fault, e.g. stack overflow or invalid memory access)"
+        }
+        libc::SIGFPE => "SIGFPE (arithmetic error, e.g. integer division by zero)",
+        libc::SIGABRT => "SIGABRT (aborted, e.g. failed assertion or allocation)",
+        libc::SIGKILL => "SIGKILL (killed)",
+        libc::SIGBUS => "SIGBUS (bus error)",
+        libc::SIGILL => "SIGILL (illegal instruction)",
+        libc::SIGPIPE => "SIGPIPE (broken pipe)",
+        libc::SIGSYS => "SIGSYS (forbidden system call)",
+        libc::SIGXCPU => "SIGXCPU (cpu time limit exceeded)",
+        libc::SIGXFSZ => "SIGXFSZ (output limit exceeded)",
+        r => return format!("killed by signal {}", r),
+    };
+    format!("killed by {}", name)
+}
+//the verdict of a run that broke a limit of case or did not end normally,None otherwise
+pub fn limit_verdict(
+    run_status: &RunStatus,
+    case: &Case,
+    output_limit: Option<i32>,
+    output_size: u64,
+) -> Option<(EnumResult, String)> {
+    let cpu_exceeded = run_status.time > Duration::from_micros(case.time_limit as u64)
+        || run_status.status.signal() == Some(libc::SIGXCPU);
+    if run_status.timed_out && !cpu_exceeded {
+        Some((EnumResult::IdlenessLimitExceeded, String::new()))
+    } else if cpu_exceeded {
+        Some((EnumResult::TimeLimitExceeded, String::new()))
+    } else if run_status.status.signal() == Some(libc::SIGXFSZ)
+        || output_limit.is_some_and(|r| output_size > r as u64)
+    {
+        Some((EnumResult::OutputLimitExceeded, String::new()))
+    } else if case.memory_limit > 0 && run_status.memory > case.memory_limit as u64 {
+        Some((EnumResult::MemoryLimitExceeded, String::new()))
+    } else if !run_status.status.success() {
+        Some((
+            EnumResult::RuntimeError,
+            describe_exit_status(&run_status.status),
+        ))
+    } else {
+        None
+    }
+}
+pub struct RunStatus {
+    pub status: ExitStatus,
+    pub time: Duration,
+    pub memory: u64,
+    pub timed_out: bool,
+}
+fn set_rlimit(command: &mut Command, resource: libc::__rlimit_resource_t, soft: u64, hard: u64) {
+    unsafe {
+        command.pre_exec(move || {
+            let limit = libc::rlimit {
+                rlim_cur: soft,
+                rlim_max: hard,
+            };
+            if libc::setrlimit(resource, &limit) < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+//kill the child by RLIMIT_CPU once it has used up its cpu time(rounded up to seconds)
+pub fn set_cpu_limit(command: &mut Command, time_limit: i32) {
+    let seconds = (time_limit as u64).div_ceil(1000000).max(1);
+    set_rlimit(command, libc::RLIMIT_CPU, seconds, seconds + 1);
+}
+//make allocations of the child fail beyond memory_limit bytes
+pub fn set_memory_limit(command: &mut Command, memory_limit: i32) {
+    set_rlimit(
+        command,
+        libc::RLIMIT_DATA,
+        memory_limit as u64,
+        memory_limit as u64,
+    );
+}
+//bound the memory of a run above memory_limit,leaving room for memory it reserves without
+//using;the limit stops runaway programs,the peak memory decides the verdict
+pub fn set_run_memory_limit(command: &mut Command, memory_limit: i32) {
+    if memory_limit <= 0 {
+        return;
+    }
+    let limit = memory_limit as u64 + (memory_limit as u64).max(MEMORY_HEADROOM);
+    set_rlimit(command, libc::RLIMIT_DATA, limit, limit);
+}
+//stop the child from writing more than one byte beyond output_limit to a file,so the size of the
+//output tells a run that went over from one that wrote exactly output_limit bytes;it is killed by
+//SIGXFSZ unless it ignores the signal and the write fails instead
+pub fn set_output_limit(command: &mut Command, output_limit: i32) {
+    let limit = output_limit as u64 + 1;
+    set_rlimit(command, libc::RLIMIT_FSIZE, limit, limit);
+}
+//resident memory in bytes of the largest process of the run of pid;a sandbox mounts a procfs of
+//its own,listing the processes of the run and its reaper as pid 1,otherwise pid alone is counted
+fn run_memory(pid: libc::pid_t) -> u64 {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+    let resident = |path: &Path| {
+        let statm = std::fs::read_to_string(path).unwrap_or_default();
+        let pages = statm
+            .split_whitespace()
+            .nth(1)
+            .and_then(|i| i.parse::<u64>().ok());
+        pages.unwrap_or(0) * page_size
+    };
+    let run_proc = PathBuf::from(format!("/proc/{}/root/proc", pid));
+    let is_sandboxed = match (std::fs::metadata(&run_proc), std::fs::metadata("/proc")) {
+        (Ok(r), Ok(own)) => r.dev() != own.dev(),
+        _ => false,
+    };
+    if !is_sandboxed {
+        return resident(Path::new(&format!("/proc/{}/statm", pid)));
+    }
+    let entries = match std::fs::read_dir(&run_proc) {
+        Ok(r) => r,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|i| i.ok())
+        .filter(|i| {
+            let id = i.file_name().to_str().and_then(|r| r.parse::<u32>().ok());
+            id.is_some_and(|r| r > 1)
+        })
+        .map(|i| resident(&i.path().join("statm")))
+        .max()
+        .unwrap_or(0)
+}
+//wait for child with a wall clock limit,keeping the rusage of the child(cpu time,peak memory in bytes)
+//the child is also killed once cancel is set
+pub fn wait_with_limit(
+    child: &mut Child,
+    duration: Duration,
+    cancel: &CancelFlag,
+) -> Result<RunStatus, std::io::Error> {
+    wait_with_memory_limit(child, duration, 0, cancel)
+}
+//wait_with_limit that also kills child once a process of its run holds more than memory_limit
+//bytes(none if not positive);an allocation beyond the limit then fails by touching it rather than
+//outright,so the run is still judged by its peak memory,which includes the largest one seen
+pub fn wait_with_memory_limit(
+    child: &mut Child,
+    duration: Duration,
+    memory_limit: i32,
+    cancel: &CancelFlag,
+) -> Result<RunStatus, std::io::Error> {
+    let pid = child.id() as libc::pid_t;
+    let (sender, receiver) = channel::<()>();
+    let cancel = cancel.clone();
+    let watchdog = std::thread::spawn(move || {
+        let deadline = Instant::now() + duration;
+        let mut peak_memory = 0;
+        loop {
+            if memory_limit > 0 {
+                peak_memory = peak_memory.max(run_memory(pid));
             }
-            for i in 0..vec_ans.len() {
-                match vec_ans[i].trim_end().cmp(&mut vec_out[i].trim_end()) {
-                    std::cmp::Ordering::Equal => {
-                        continue;
-                    }
-                    _ => {
-                        return Ok(EnumResult::WrongAnswer);
-                    }
+            let now = Instant::now();
+            let is_timeout = now >= deadline;
+            let is_over = memory_limit > 0 && peak_memory > memory_limit as u64;
+            if is_timeout || is_over || cancel.load(Ordering::SeqCst) {
+                unsafe {
+                    libc::kill(pid, libc::SIGKILL);
                 }
+                return (is_timeout, peak_memory);
             }
-            return Ok(EnumResult::Accepted);
+            let interval = if memory_limit > 0 {
+                MEMORY_POLL_INTERVAL
+            } else {
+                CANCEL_POLL_INTERVAL
+            };
+            let step = (deadline - now).min(interval);
+            if let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(step) {
+                continue;
+            }
+            return (false, peak_memory);
         }
+    });
+    let res_use = child.wait4();
+    let _ = sender.send(());
+    let (killed, peak_memory) = watchdog.join().unwrap_or((false, 0));
+    let res_use = res_use?;
+    Ok(RunStatus {
+        timed_out: killed && res_use.status.signal() == Some(libc::SIGKILL),
+        status: res_use.status,
+        time: res_use.rusage.utime + res_use.rusage.stime,
+        memory: res_use.rusage.maxrss.max(peak_memory),
+    })
+}
+fn check_canceled(cancel: &CancelFlag) -> Result<(), Box<dyn std::error::Error>> {
+    if cancel.load(Ordering::SeqCst) {
+        return Err("job canceled".into());
     }
+    Ok(())
+}
+//the compile steps of language,none for interpreted languages
+pub fn compile_steps(language: &Language) -> Vec<Vec<String>> {
+    let steps = match &language.compile_steps {
+        Some(r) => r.clone(),
+        None => vec![language.command.clone()],
+    };
+    steps.into_iter().filter(|i| !i.is_empty()).collect()
 }
-pub fn match_result_spj(list: Vec<String>) -> (EnumResult, String) {
-    let str1 = list[0].clone();
-    let mut arg_list = Vec::new();
-    for i in 1..list.len() {
-        arg_list.push(list[i].clone());
+//run the compile steps of language in folder_name with their output going to compile_out,
+//stopping at the first that fails;the steps share time_limit
+pub fn run_compile_steps(
+    folder_name: &str,
+    language: &Language,
+    compile_out: &File,
+    time_limit: i32,
+    cancel: &CancelFlag,
+) -> Result<RunStatus, std::io::Error> {
+    let deadline = Instant::now() + Duration::from_micros(time_limit as u64);
+    let mut status = Err(std::io::Error::other("no compile step"));
+    for step in compile_steps(language) {
+        let mut args_vec = fill_placeholders(&step, folder_name, language);
+        let mut command = Command::new(args_vec.remove(0));
+        command
+            .args(args_vec)
+            .stdout(Stdio::from(compile_out.try_clone()?))
+            .stderr(Stdio::from(compile_out.try_clone()?));
+        if let Some(r) = language.compile_memory_limit {
+            set_memory_limit(&mut command, r);
+        }
+        status = command.spawn().and_then(|mut child| {
+            wait_with_limit(
+                &mut child,
+                deadline.saturating_duration_since(Instant::now()),
+                cancel,
+            )
+        });
+        match &status {
+            Ok(r) if !r.timed_out && r.status.success() => {}
+            _ => break,
+        }
     }
-    let mut command = Command::new(str1);
-    command.args(arg_list);
-    let out = command.output();
-    match out {
-        Err(_r) => {
-            return (EnumResult::SPJError, String::new());
+    status
+}
+//fill in the placeholders of args for a program of language kept in folder
+pub fn fill_placeholders(args: &[String], folder: &str, language: &Language) -> Vec<String> {
+    args.iter()
+        .map(|i| {
+            i.replace("%INPUT%", &format!("{}/{}", folder, language.file_name))
+                .replace("%OUTPUT%", &format!("{}/test", folder))
+                .replace("%FOLDER%", folder)
+        })
+        .collect()
+}
+//the command running the program compiled into folder_name,inside the sandbox of language
+pub fn run_command(folder_name: &str, language: &Language) -> Result<Command, std::io::Error> {
+    let template = match &language.run {
+        Some(r) => r.clone(),
+        None => vec!["%OUTPUT%".to_string()],
+    };
+    let args = fill_placeholders(
+        &template,
+        sandbox_folder(folder_name, &language.sandbox),
+        language,
+    );
+    sandbox_command(folder_name, args, &language.sandbox)
+}
+//the folder inside the job folder that a submission is compiled and run in;the judge keeps its
+//own files outside of it,where a sandboxed program cannot plant links for the judge to follow
+fn create_program_folder(folder_name: &str) -> Result<String, std::io::Error> {
+    let program_folder = format!("{}/program", folder_name);
+    std::fs::create_dir(&program_folder)?;
+    Ok(program_folder)
+}
+//compile source_code in folder_name with the compile steps of language,
+//returning whether it succeeded and the compiler's output
+pub fn compile_submission(
+    folder_name: &str,
+    source_code: &str,
+    language: &Language,
+    cancel: &CancelFlag,
+) -> Result<(bool, String), Box<dyn std::error::Error>> {
+    let src_path = format!("{}/{}", folder_name, language.file_name);
+    let mut buffer = std::fs::File::create(src_path)?;
+    buffer.write_all(source_code.as_bytes())?;
+    //interpreted languages run the source itself
+    if compile_steps(language).is_empty() {
+        return Ok((true, String::new()));
+    }
+    let compile_info_limit = match language.compile_info_limit {
+        Some(r) => r,
+        None => DEFAULT_COMPILE_INFO_LIMIT,
+    };
+    let compile_out_path = format!("{}/compile.out", folder_name);
+    //rejudges and duplicate submissions reuse an earlier compilation
+    let key = compile_cache::cache_key(source_code, language);
+    if let Some(is_compiled) = compile_cache::load(&key, folder_name) {
+        let info = read_truncated(&compile_out_path, compile_info_limit as usize)?;
+        return Ok((is_compiled, info));
+    }
+    //capture the compiler's stdout and stderr
+    let compile_out = File::create(&compile_out_path)?;
+    let compile_time_limit = match language.compile_time_limit {
+        Some(r) => r,
+        None => DEFAULT_COMPILE_TIME_LIMIT,
+    };
+    let status = run_compile_steps(
+        folder_name,
+        language,
+        &compile_out,
+        compile_time_limit,
+        cancel,
+    );
+    check_canceled(cancel)?;
+    let mut info = read_truncated(&compile_out_path, compile_info_limit as usize)?;
+    let is_compiled = match status {
+        Err(r) => {
+            info = r.to_string();
+            false
         }
-        Ok(s) => {
-            if !s.status.success() {
-                return (EnumResult::SPJError, String::from_utf8(s.stderr).unwrap());
+        Ok(r) => {
+            if r.timed_out {
+                info.insert_str(0, "Compilation time limit exceeded\n");
             } else {
-                let full_string = String::from_utf8(s.stdout).unwrap();
-                let vec_str: Vec<&str> = full_string.split('\n').collect();
-                if vec_str.len() <= 1 {
-                    return (EnumResult::SPJError, String::new());
-                }
-                let mut parse_str = "\"".to_string();
-                parse_str.push_str(vec_str[0]);
-                parse_str.push('\"');
-                let enum_res: Result<EnumResult, serde_json::Error> =
-                    serde_json::from_str(&parse_str);
-                match enum_res {
-                    Ok(i) => {
-                        return (i, vec_str[1].to_string());
-                    }
-                    Err(_r) => {
-                        return (EnumResult::SPJError, vec_str[1].to_string());
-                    }
-                }
+                //a timeout may come from a busy machine,so only finished compilations are kept
+                compile_cache::store(&key, folder_name, r.status.success());
             }
+            !r.timed_out && r.status.success()
         }
-    }
+    };
+    Ok((is_compiled, info))
 }
 pub fn execute_input_inner(
     mut message: Message,
     mut pool: web::Data<Pool<SqliteConnectionManager>>,
     problem: Problem,
     language: Language,
-) -> Result<(), Box<dyn std::error::Error>> {
+    cancel: &CancelFlag,
+) -> Result<Message, Box<dyn std::error::Error>> {
     let task_id = message.id;
     std::fs::create_dir(format!("temp{}", task_id))?;
     let folder_name = format!("temp{}", task_id);
-    let src_path = format!("temp{}/{}", task_id, language.file_name);
-    let mut buffer = std::fs::File::create(src_path.clone())?;
-    buffer
-        .write(message.submission.source_code.as_bytes())
-        .unwrap();
-    let mut args_vec = language.command.clone();
-    for i in &mut args_vec {
-        if i == "%OUTPUT%" {
-            *i = format!("{}/test", folder_name);
-        } else if i == "%INPUT%" {
-            *i = src_path.clone();
-        }
-    }
-    let first_arg = args_vec.remove(0);
+    let program_folder = create_program_folder(&folder_name)?;
     let compile_time_start = Utc::now();
     message.state = State::Running;
     message.result = EnumResult::Running;
@@ -157,7 +698,7 @@ pub fn execute_input_inner(
         .to_string();
     //update task TABLE
     let _ = pool.get()?.execute(
-        "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4 WHERE id=?5",
+        "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4 WHERE id=?5 AND state='Running'",
         (
             "Running".to_string(),
             "Running".to_string(),
@@ -166,32 +707,22 @@ pub fn execute_input_inner(
             message.id,
         ),
     );
-    let status = Command::new(first_arg).args(args_vec).status();
+    let is_compiled;
+    (is_compiled, message.cases[0].info) = compile_submission(
+        &program_folder,
+        &message.submission.source_code,
+        &language,
+        cancel,
+    )?;
     let updated_time = Utc::now();
     //update status
     message.updated_time = updated_time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-    if let Err(_r) = status {
-        message.state = State::Finished;
-        message.result = EnumResult::CompilationError;
-        message.cases[0].result = EnumResult::CompilationError;
-        let _ = pool.get()?.execute(
-            "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4 WHERE id=?5",
-            (
-                "Finished".to_string(),
-                "Compilation Error".to_string(),
-                serde_json::to_string(&message.cases)?,
-                message.updated_time.clone(),
-                message.id,
-            ),
-        );
-        std::fs::remove_dir_all(folder_name)?;
-        return Ok(());
-    } else if !status.unwrap().success() {
+    if !is_compiled {
         message.state = State::Finished;
         message.result = EnumResult::CompilationError;
         message.cases[0].result = EnumResult::CompilationError;
         let _ = pool.get()?.execute(
-            "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4 WHERE id=?5",
+            "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4 WHERE id=?5 AND state='Running'",
             (
                 "Finished".to_string(),
                 "Compilation Error".to_string(),
@@ -201,39 +732,41 @@ pub fn execute_input_inner(
             ),
         );
         std::fs::remove_dir_all(folder_name)?;
-        return Ok(());
+        return Ok(message);
     }
     message.cases[0].result = EnumResult::CompilationSuccess;
     //update task table
     let _ = pool.get()?.execute(
-        "UPDATE task SET cases=?1,updated_time=?2 WHERE id=?3",
+        "UPDATE task SET cases=?1,updated_time=?2 WHERE id=?3 AND state='Running'",
         (
             serde_json::to_string(&message.cases)?,
             message.updated_time.clone(),
             message.id,
         ),
     );
-    let execute_path = {
-        let mut i = PathBuf::new();
-        i.push(folder_name.clone());
-        i.push("test".to_string());
-        let i: PathBuf = i.iter().collect();
-        i
-    };
-    let mut index = 1;
-    //check if misc has packing
-    let mut has_packing = false;
-    let mut vec_packing: Vec<Vec<i32>> = Vec::new();
-    if let Some(i) = &problem.misc {
-        if let Some(r) = &i.packing {
-            vec_packing = r.clone();
-            has_packing = true;
-        }
-    }
+    let subtasks = problem_subtasks(&problem);
+    let checker_protocol = problem
+        .misc
+        .as_ref()
+        .and_then(|r| r.checker_protocol.clone());
+    let spj_memory_limit = problem.misc.as_ref().and_then(|r| r.spj_memory_limit);
+    let comparator = problem.misc.as_ref().and_then(|r| r.comparator.clone());
+    let presentation_error = problem
+        .misc
+        .as_ref()
+        .and_then(|r| r.presentation_error)
+        .unwrap_or(false);
+    let is_diagnostics_public = matches!(
+        problem.misc.as_ref().and_then(|r| r.diagnostics.clone()),
+        Some(Visibility::Contestant)
+    );
+    //where each wrong output differs from the answer,indexed like cases
+    let mut diagnostics = vec![String::new(); message.cases.len()];
     //start executing program
-    for i in &problem.cases {
-        if let EnumResult::Skipped = message.cases[index].result {
-            index += 1;
+    for index in judge_order(&problem, &subtasks) {
+        let i = &problem.cases[index - 1];
+        if !is_case_needed(index, &subtasks, &message.cases) {
+            message.cases[index].result = EnumResult::Skipped;
             continue;
         }
         let out_path = {
@@ -245,128 +778,191 @@ pub fn execute_input_inner(
         };
         let out_file = File::create(&out_path)?;
         let in_file = File::open(&i.input_file)?;
-        let mut child = Command::new(&execute_path)
-            .stdin(Stdio::from(in_file))
-            .stdout(Stdio::from(out_file))
-            .spawn()?;
-        let execute_start = Instant::now();
-        let duration = Duration::from_micros(i.time_limit as u64);
-        match child.wait_timeout(duration)? {
-            Some(child_status) => {
-                let execute_end = Instant::now();
-                let t = execute_end.duration_since(execute_start);
-                message.cases[index].time = t.as_micros() as i32;
-                if !child_status.success() {
-                    message.cases[index].result = EnumResult::RuntimeError;
-                    if let EnumResult::Running=message.result{
-                        message.result=EnumResult::RuntimeError;
-                    }
-                } else {
-                    //if spj
-                    if let ProblemType::Spj = problem.ty {
-                        match &problem.misc {
-                            None => {}
-                            Some(i) => match &i.special_judge {
-                                None => {}
-                                Some(v) => {
-                                    let mut vec_args = v.clone();
-                                    for i in &mut vec_args {
-                                        if i == "%OUTPUT%" {
-                                            *i = out_path.to_str().unwrap().to_string();
-                                        } else if i == "%ANSWER%" {
-                                            *i = problem.cases[index - 1].answer_file.clone();
-                                        }
-                                    }
-                                    (message.cases[index].result, message.cases[index].info) =
-                                        match_result_spj(vec_args);
-                                }
-                            },
-                        }
-                    } else {
-                        message.cases[index].result = match_result(
-                            out_path.to_str().unwrap().to_string(),
-                            problem.cases[index - 1].answer_file.clone(),
-                            problem.ty.clone(),
-                        )?;
-                    }//assign message.result
-                    if let EnumResult::Running=message.result{
-                        if let EnumResult::Accepted=message.cases[index].result{
-                        }else{
-                            message.result=message.cases[index].result.clone();
-                        }
-                    }
-                    if let EnumResult::Accepted = message.cases[index].result {
-                        message.score += problem.cases[index - 1].score;
-                    }
+        let mut command = run_command(&program_folder, &language)?;
+        set_cpu_limit(&mut command, i.time_limit);
+        //the wall clock only guards against programs that sleep or block
+        let wall_time_limit = match i.wall_time_limit {
+            Some(r) => r,
+            None => i.time_limit * 2 + 1000000,
+        };
+        //interactive problems cross-wire the submission with the interactor
+        let report_path = {
+            let mut i = PathBuf::new();
+            i.push(folder_name.clone());
+            i.push(format!("{}.report", index));
+            let i: PathBuf = i.iter().collect();
+            i
+        };
+        let mut interactor = None;
+        if let ProblemType::Interactive = problem.ty {
+            let mut vec_args = match &problem.misc {
+                Some(r) => match &r.interactor {
+                    Some(v) => v.clone(),
+                    None => return Err("interactor not configured".into()),
+                },
+                None => return Err("interactor not configured".into()),
+            };
+            for j in &mut vec_args {
+                if j == "%INPUT%" {
+                    *j = i.input_file.clone();
+                } else if j == "%OUTPUT%" {
+                    *j = out_path.to_str().unwrap().to_string();
+                } else if j == "%ANSWER%" {
+                    *j = i.answer_file.clone();
                 }
             }
-            None => {
-                message.cases[index].time = i.time_limit;
-                message.cases[index].result = EnumResult::TimeLimitExceeded;
-                if let EnumResult::Running=message.result{
-                    message.result=EnumResult::TimeLimitExceeded;
+            let first_arg = vec_args.remove(0);
+            let mut interactor_command = Command::new(first_arg);
+            if let Some(r) = spj_memory_limit {
+                set_run_memory_limit(&mut interactor_command, r);
+            }
+            let mut child = interactor_command
+                .args(vec_args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::from(File::create(&report_path)?))
+                .spawn()?;
+            command
+                .stdin(Stdio::from(child.stdout.take().unwrap()))
+                .stdout(Stdio::from(child.stdin.take().unwrap()));
+            interactor = Some(child);
+        } else {
+            command
+                .stdin(Stdio::from(in_file))
+                .stdout(Stdio::from(out_file));
+        }
+        //the stderr of the judge may be a log file,which the output limit would apply to
+        command.stderr(Stdio::null());
+        let output_limit = match i.output_limit {
+            Some(r) => Some(r),
+            None => problem.output_limit,
+        };
+        if let Some(r) = output_limit {
+            set_output_limit(&mut command, r);
+        }
+        let spawned = command.spawn();
+        //close the pipe ends held by command,so each side sees EOF when the other exits
+        drop(command);
+        let mut child = match spawned {
+            Ok(r) => r,
+            Err(r) => {
+                if let Some(mut c) = interactor {
+                    let _ = c.kill();
+                    let _ = c.wait();
                 }
-                child.kill()?;
-                child.wait()?;
+                return Err(r.into());
+            }
+        };
+        let run_status = wait_with_memory_limit(
+            &mut child,
+            Duration::from_micros(wall_time_limit as u64),
+            i.memory_limit,
+            cancel,
+        )?;
+        let interactor_result = match interactor {
+            Some(r) => Some(match_result_interactor(
+                r,
+                &report_path,
+                Duration::from_micros(wall_time_limit as u64),
+                cancel,
+                &checker_protocol,
+                spj_memory_limit,
+            )?),
+            None => None,
+        };
+        check_canceled(cancel)?;
+        message.cases[index].time = run_status.time.as_micros() as i32;
+        message.cases[index].memory = run_status.memory.min(i32::MAX as u64) as i32;
+        let output_size = std::fs::metadata(&out_path)?.len();
+        let mut verdict = limit_verdict(&run_status, i, output_limit, output_size);
+        //writing on after the interactor gave up breaks the pipe,its verdict tells why
+        if let (Some((EnumResult::RuntimeError, _)), Some((r, _, _))) =
+            (&verdict, &interactor_result)
+        {
+            if !matches!(r, EnumResult::Accepted) {
+                verdict = None;
             }
         }
-        //if misc and not accepted,update skipped
-        if has_packing {
-            match message.cases[index].result {
-                EnumResult::Accepted => {}
-                _ => {
-                    //locate index;
-                    let mut row = 0;
-                    let mut col = 0;
-                    for i in 0..vec_packing.len() {
-                        for j in 0..vec_packing[i].len() {
-                            if vec_packing[i][j] as usize == index {
-                                row = i;
-                                col = j;
-                                break;
+        if let Some((result, info)) = verdict {
+            message.cases[index].result = result.clone();
+            message.cases[index].info = info;
+            if let EnumResult::Running = message.result {
+                message.result = result;
+            }
+        } else {
+            //if spj
+            if let Some(r) = interactor_result {
+                (
+                    message.cases[index].result,
+                    message.cases[index].info,
+                    message.cases[index].score_ratio,
+                ) = r;
+            } else if let ProblemType::Spj = problem.ty {
+                match &problem.misc {
+                    None => {}
+                    Some(i) => match &i.special_judge {
+                        None => {}
+                        Some(v) => {
+                            let mut vec_args = v.clone();
+                            for i in &mut vec_args {
+                                if i == "%INPUT%" {
+                                    *i = problem.cases[index - 1].input_file.clone();
+                                } else if i == "%OUTPUT%" {
+                                    *i = out_path.to_str().unwrap().to_string();
+                                } else if i == "%ANSWER%" {
+                                    *i = problem.cases[index - 1].answer_file.clone();
+                                }
                             }
+                            (
+                                message.cases[index].result,
+                                message.cases[index].info,
+                                message.cases[index].score_ratio,
+                            ) = match_result_spj(vec_args, i, &out_path, cancel)?;
                         }
-                    }
-                    //assign others in the pack status skipped
-                    for i in col + 1..vec_packing[row].len() {
-                        message.cases[vec_packing[row][i] as usize].result = EnumResult::Skipped;
-                    }
+                    },
+                }
+            } else {
+                let (result, difference) = match_result(
+                    out_path.to_str().unwrap().to_string(),
+                    problem.cases[index - 1].answer_file.clone(),
+                    problem.ty.clone(),
+                    &comparator,
+                    presentation_error,
+                )?;
+                message.cases[index].result = result;
+                if is_diagnostics_public {
+                    message.cases[index].info = difference.clone();
+                }
+                diagnostics[index] = difference;
+            } //assign message.result
+            if let EnumResult::Running = message.result {
+                if let EnumResult::Accepted = message.cases[index].result {
+                } else {
+                    message.result = message.cases[index].result.clone();
                 }
             }
         }
-        index += 1;
+        (message.score, message.subtasks) = score_job(&problem, &subtasks, &message.cases);
         //update task TABLE
         let updated_time = Utc::now();
         message.updated_time = updated_time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
         pool = pool.clone();
         let _ = pool.get()?.execute(
-            "UPDATE task SET cases=?1,updated_time=?2,score=?3,result=?5 WHERE id=?4",
+            "UPDATE task SET cases=?1,updated_time=?2,score=?3,result=?5,subtasks=?6,diagnostics=?7 WHERE id=?4 AND state='Running'",
             (
                 serde_json::to_string(&message.cases)?,
                 message.updated_time.clone(),
                 message.score,
                 message.id,
-                message.result.to_string()
+                message.result.to_string(),
+                serde_json::to_string(&message.subtasks)?,
+                serde_json::to_string(&diagnostics)?,
             ),
         );
     }
     //update final result
     message.state = State::Finished;
-    //patch has_packing
-    if has_packing {
-        for i in &vec_packing {
-            match message.cases[i[i.len() - 1] as usize].result {
-                EnumResult::Skipped => {
-                    for j in 0..i.len() {
-                        if let EnumResult::Accepted = message.cases[i[j] as usize].result {
-                            message.score -= problem.cases[i[j] as usize - 1].score;
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
+    (message.score, message.subtasks) = score_job(&problem, &subtasks, &message.cases);
     //patch dynamic_ranking
     if let ProblemType::DynamicRanking = problem.ty {
         if let Some(r) = problem.misc {
@@ -382,6 +978,11 @@ pub fn execute_input_inner(
             EnumResult::Accepted => {
                 continue;
             }
+            EnumResult::PartiallyAccepted => {
+                message.result = EnumResult::PartiallyAccepted;
+                is_accepted = false;
+                break;
+            }
             EnumResult::RuntimeError => {
                 message.result = EnumResult::RuntimeError;
                 is_accepted = false;
@@ -392,6 +993,11 @@ pub fn execute_input_inner(
                 is_accepted = false;
                 break;
             }
+            EnumResult::PresentationError => {
+                message.result = EnumResult::PresentationError;
+                is_accepted = false;
+                break;
+            }
             EnumResult::SPJError => {
                 message.result = EnumResult::SPJError;
                 is_accepted = false;
@@ -402,6 +1008,21 @@ pub fn execute_input_inner(
                 is_accepted = false;
                 break;
             }
+            EnumResult::MemoryLimitExceeded => {
+                message.result = EnumResult::MemoryLimitExceeded;
+                is_accepted = false;
+                break;
+            }
+            EnumResult::IdlenessLimitExceeded => {
+                message.result = EnumResult::IdlenessLimitExceeded;
+                is_accepted = false;
+                break;
+            }
+            EnumResult::OutputLimitExceeded => {
+                message.result = EnumResult::OutputLimitExceeded;
+                is_accepted = false;
+                break;
+            }
             _ => {}
         }
     }
@@ -411,35 +1032,118 @@ pub fn execute_input_inner(
     //update final result in task table
     pool = pool.clone();
     let _ = pool.get()?.execute(
-        "UPDATE task SET state=?1,result=?2,updated_time=?3,score=?4 WHERE id=?5",
+        "UPDATE task SET state=?1,result=?2,updated_time=?3,score=?4,cases=?6,subtasks=?7,diagnostics=?8 WHERE id=?5 AND state='Running'",
         (
             "Finished".to_string(),
             message.result.to_string(),
             message.updated_time.clone(),
             message.score,
             message.id,
+            serde_json::to_string(&message.cases)?,
+            serde_json::to_string(&message.subtasks)?,
+            serde_json::to_string(&diagnostics)?,
         ),
     );
     std::fs::remove_dir_all(folder_name)?;
-    Ok(())
+    Ok(message)
 }
 pub fn execute_input(
     message: Message,
     pool: web::Data<Pool<SqliteConnectionManager>>,
     problem: Problem,
     language: Language,
+    cancel: &CancelFlag,
 ) {
     let id = message.id;
-    match execute_input_inner(message, pool.clone(), problem, language) {
+    match execute_input_inner(message, pool.clone(), problem, language, cancel) {
         Ok(_ok) => {}
         Err(_r) => {
+            //a canceled job is no longer Running,so its row is left alone
+            let _ = std::fs::remove_dir_all(format!("temp{}", id));
             pool.get()
                 .unwrap()
                 .execute(
-                    "UPDATE task SET result=?1 WHERE id=?2",
-                    (EnumResult::SystemError.to_string(), id),
+                    "UPDATE task SET state=?1,result=?2 WHERE id=?3 AND state='Running'",
+                    (
+                        State::Finished.to_string(),
+                        EnumResult::SystemError.to_string(),
+                        id,
+                    ),
                 )
                 .unwrap();
         }
     }
 }
+//compile run.source_code and execute it once on run.input under the limits of the first case
+pub fn run_custom(
+    run: &CustomRun,
+    problem: &Problem,
+    language: &Language,
+) -> Result<RunResult, Box<dyn std::error::Error>> {
+    let folder_name = format!("temp_run{}", RUN_COUNTER.fetch_add(1, Ordering::SeqCst));
+    std::fs::create_dir(&folder_name)?;
+    let result = run_custom_inner(run, problem, language, &folder_name);
+    let _ = std::fs::remove_dir_all(&folder_name);
+    result
+}
+fn run_custom_inner(
+    run: &CustomRun,
+    problem: &Problem,
+    language: &Language,
+    folder_name: &str,
+) -> Result<RunResult, Box<dyn std::error::Error>> {
+    let case = problem.cases.first().ok_or("problem has no cases")?;
+    let cancel = CancelFlag::default();
+    let program_folder = create_program_folder(folder_name)?;
+    let (is_compiled, info) =
+        compile_submission(&program_folder, &run.source_code, language, &cancel)?;
+    if !is_compiled {
+        return Ok(RunResult {
+            result: EnumResult::CompilationError,
+            time: 0,
+            memory: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            info,
+        });
+    }
+    let in_path = format!("{}/run.in", folder_name);
+    let out_path = format!("{}/run.out", folder_name);
+    let err_path = format!("{}/run.err", folder_name);
+    std::fs::write(&in_path, &run.input)?;
+    let mut command = run_command(&program_folder, language)?;
+    set_cpu_limit(&mut command, case.time_limit);
+    let wall_time_limit = match case.wall_time_limit {
+        Some(r) => r,
+        None => case.time_limit * 2 + 1000000,
+    };
+    let output_limit = match case.output_limit {
+        Some(r) => Some(r),
+        None => problem.output_limit,
+    };
+    if let Some(r) = output_limit {
+        set_output_limit(&mut command, r);
+    }
+    command
+        .stdin(Stdio::from(File::open(&in_path)?))
+        .stdout(Stdio::from(File::create(&out_path)?))
+        .stderr(Stdio::from(File::create(&err_path)?));
+    let mut child = command.spawn()?;
+    let run_status = wait_with_memory_limit(
+        &mut child,
+        Duration::from_micros(wall_time_limit as u64),
+        case.memory_limit,
+        &cancel,
+    )?;
+    let output_size = std::fs::metadata(&out_path)?.len();
+    let (result, info) = limit_verdict(&run_status, case, output_limit, output_size)
+        .unwrap_or((EnumResult::Accepted, String::new()));
+    Ok(RunResult {
+        result,
+        time: run_status.time.as_micros() as i32,
+        memory: run_status.memory.min(i32::MAX as u64) as i32,
+        stdout: read_truncated(&out_path, RUN_OUTPUT_LIMIT)?,
+        stderr: read_truncated(&err_path, RUN_OUTPUT_LIMIT)?,
+        info,
+    })
+}
@@ -0,0 +1,126 @@
+use super::compile_cache::digest;
+use super::configure::{Configure, Generator};
+use super::execute::{describe_exit_status, read_truncated, wait_with_limit};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+const DATA_CACHE_DIR: &str = "cache/data";
+const DEFAULT_GENERATOR_TIME_LIMIT: i32 = 60000000;
+//identify the data by the commands and the content of the files they name
+fn generator_hash(generator: &Generator) -> String {
+    let mut fields = Vec::new();
+    for command in [&generator.command, &generator.solution] {
+        fields.push(serde_json::to_vec(command).unwrap());
+        for i in command {
+            if let Ok(r) = std::fs::read(i) {
+                fields.push(r);
+            }
+        }
+    }
+    digest(fields.iter().map(|i| i.as_slice()))
+}
+//run command with stdin and stdout redirected,its stderr explains a failure
+fn run_command(
+    command: &[String],
+    stdin: Stdio,
+    stdout: &Path,
+    err_path: &Path,
+) -> Result<(), String> {
+    let mut vec_args = command.to_vec();
+    if vec_args.is_empty() {
+        return Err("empty command".to_string());
+    }
+    let mut child = Command::new(vec_args.remove(0))
+        .args(vec_args)
+        .stdin(stdin)
+        .stdout(Stdio::from(
+            File::create(stdout).map_err(|r| r.to_string())?,
+        ))
+        .stderr(Stdio::from(
+            File::create(err_path).map_err(|r| r.to_string())?,
+        ))
+        .spawn()
+        .map_err(|r| format!("cannot run {}: {}", command.join(" "), r))?;
+    let status = wait_with_limit(
+        &mut child,
+        Duration::from_micros(DEFAULT_GENERATOR_TIME_LIMIT as u64),
+        &Arc::new(AtomicBool::new(false)),
+    )
+    .map_err(|r| r.to_string())?;
+    if status.timed_out || !status.status.success() {
+        let info = read_truncated(err_path.to_str().unwrap(), 4096).unwrap_or_default();
+        return Err(format!(
+            "{}: {}\n{}",
+            command.join(" "),
+            if status.timed_out {
+                "time limit exceeded".to_string()
+            } else {
+                describe_exit_status(&status.status)
+            },
+            info
+        ));
+    }
+    Ok(())
+}
+//the input and answer files of generator,reusing the data of an earlier run with the same hash
+fn generate_case(generator: &Generator) -> Result<(PathBuf, PathBuf), String> {
+    let folder = PathBuf::from(DATA_CACHE_DIR).join(generator_hash(generator));
+    let input = folder.join("input");
+    let answer = folder.join("answer");
+    if folder.exists() {
+        return Ok((input, answer));
+    }
+    //generate in a scratch folder,so interrupted generation is never taken for cached data
+    let build_folder = folder.with_extension("build");
+    let _ = std::fs::remove_dir_all(&build_folder);
+    std::fs::create_dir_all(&build_folder).map_err(|r| r.to_string())?;
+    let err_path = build_folder.join("generator.err");
+    run_command(
+        &generator.command,
+        Stdio::null(),
+        &build_folder.join("input"),
+        &err_path,
+    )?;
+    let in_file = File::open(build_folder.join("input")).map_err(|r| r.to_string())?;
+    run_command(
+        &generator.solution,
+        Stdio::from(in_file),
+        &build_folder.join("answer"),
+        &err_path,
+    )?;
+    let _ = std::fs::remove_file(&err_path);
+    std::fs::rename(&build_folder, &folder).map_err(|r| r.to_string())?;
+    Ok((input, answer))
+}
+//fill in the data of the cases given by a generator,a failure makes the config invalid
+pub fn generate_data(config: &mut Configure) -> Result<(), String> {
+    for problem in &mut config.problems {
+        for (index, case) in problem.cases.iter_mut().enumerate() {
+            let generator = match &case.generator {
+                Some(r) => r,
+                None if case.input_file.is_empty() || case.answer_file.is_empty() => {
+                    return Err(format!(
+                        "problem {} case {} has neither data files nor a generator",
+                        problem.id,
+                        index + 1
+                    ));
+                }
+                None => continue,
+            };
+            let (input, answer) = generate_case(generator).map_err(|r| {
+                format!(
+                    "generator of problem {} case {}: {}",
+                    problem.id,
+                    index + 1,
+                    r
+                )
+            })?;
+            case.input_file = input.to_str().unwrap().to_string();
+            case.answer_file = answer.to_str().unwrap().to_string();
+        }
+    }
+    Ok(())
+}
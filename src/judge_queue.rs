@@ -0,0 +1,332 @@
+use super::configure::Configure;
+use super::enumresult::{EnumResult, State};
+use super::execute::{execute_input, message_from_row, CancelFlag, Message};
+use actix_web::web;
+use chrono::prelude::*;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension, TransactionBehavior};
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+//the task table is the queue:workers take Queueing jobs in the order they were queued
+pub struct JudgeQueue {
+    generation: Mutex<u64>,
+    condvar: Condvar,
+    //cancel flags of the jobs being judged
+    running: Mutex<HashMap<i32, CancelFlag>>,
+    //free judging slots,shared with custom runs so that no more than judge_workers programs
+    //are judged at the same time
+    slots: Mutex<usize>,
+    slot_condvar: Condvar,
+}
+//a judging slot taken from the queue,given back when dropped
+pub struct Slot<'a> {
+    queue: &'a JudgeQueue,
+}
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        *self.queue.slots.lock().unwrap() += 1;
+        self.queue.slot_condvar.notify_one();
+    }
+}
+impl JudgeQueue {
+    //wait until a judging slot is free and take it
+    pub fn slot(&self) -> Slot<'_> {
+        let guard = self.slots.lock().unwrap();
+        let mut slots = self.slot_condvar.wait_while(guard, |i| *i == 0).unwrap();
+        *slots -= 1;
+        Slot { queue: self }
+    }
+    //wake the workers after a job is put into Queueing state
+    pub fn notify(&self) {
+        let mut generation = self.generation.lock().unwrap();
+        *generation += 1;
+        self.condvar.notify_all();
+    }
+    fn wait(&self, generation: u64) {
+        let guard = self.generation.lock().unwrap();
+        //poll the table now and then,in case a job is queued behind our back
+        let _ = self
+            .condvar
+            .wait_timeout_while(guard, Duration::from_secs(5), |i| *i == generation)
+            .unwrap();
+    }
+    fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+    //claim the next job and register its cancel flag at once,so cancel() never misses it
+    fn claim(
+        &self,
+        pool: &Pool<SqliteConnectionManager>,
+    ) -> Result<Option<(Message, CancelFlag)>, Box<dyn std::error::Error>> {
+        let mut running = self.running.lock().unwrap();
+        let message = match claim_next_job(pool)? {
+            None => return Ok(None),
+            Some(r) => r,
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        running.insert(message.id, cancel.clone());
+        Ok(Some((message, cancel)))
+    }
+    //cancel a Queueing or Running job,returning the job and whether it was canceled by this call
+    pub fn cancel(
+        &self,
+        pool: &Pool<SqliteConnectionManager>,
+        id: i32,
+    ) -> Result<Option<(Message, bool)>, Box<dyn std::error::Error>> {
+        let running = self.running.lock().unwrap();
+        let mut conn = pool.get()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let message = tx
+            .query_row(
+                "SELECT * FROM task WHERE id=?1",
+                params![id],
+                message_from_row,
+            )
+            .optional()?;
+        let mut message = match message {
+            None => return Ok(None),
+            Some(r) => r,
+        };
+        match message.state {
+            State::Queueing | State::Running => {}
+            _ => return Ok(Some((message, false))),
+        }
+        //the worker kills the child and removes the temporary directory
+        if let Some(r) = running.get(&id) {
+            r.store(true, Ordering::SeqCst);
+        }
+        message.state = State::Canceled;
+        message.result = EnumResult::Skipped;
+        message.updated_time = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        for i in &mut message.cases {
+            if let EnumResult::Waiting | EnumResult::Running = i.result {
+                i.result = EnumResult::Skipped;
+            }
+        }
+        for i in &mut message.subtasks {
+            if let EnumResult::Waiting | EnumResult::Running = i.result {
+                i.result = EnumResult::Skipped;
+            }
+        }
+        tx.execute(
+            "UPDATE task SET state=?1,result=?2,cases=?3,updated_time=?4,subtasks=?6 WHERE id=?5",
+            params![
+                message.state.to_string(),
+                message.result.to_string(),
+                serde_json::to_string(&message.cases)?,
+                message.updated_time,
+                message.id,
+                serde_json::to_string(&message.subtasks)?
+            ],
+        )?;
+        tx.commit()?;
+        Ok(Some((message, true)))
+    }
+}
+//move the oldest Queueing job to Running,None if there is nothing to judge
+fn claim_next_job(
+    pool: &Pool<SqliteConnectionManager>,
+) -> Result<Option<Message>, Box<dyn std::error::Error>> {
+    let conn = pool.get()?;
+    loop {
+        let message = conn
+            .query_row(
+                "SELECT * FROM task WHERE state=?1 ORDER BY updated_time,id LIMIT 1",
+                params![State::Queueing.to_string()],
+                message_from_row,
+            )
+            .optional()?;
+        let mut message = match message {
+            None => return Ok(None),
+            Some(r) => r,
+        };
+        //another worker may have taken it in the meantime
+        let updated = conn.execute(
+            "UPDATE task SET state=?1 WHERE id=?2 AND state=?3",
+            params![
+                State::Running.to_string(),
+                message.id,
+                State::Queueing.to_string()
+            ],
+        )?;
+        if updated == 1 {
+            message.state = State::Running;
+            return Ok(Some(message));
+        }
+    }
+}
+fn judge(
+    message: Message,
+    cancel: &CancelFlag,
+    config: &Configure,
+    pool: &Pool<SqliteConnectionManager>,
+) {
+    let problem = config
+        .problems
+        .iter()
+        .find(|i| i.id == message.submission.problem_id);
+    let language = config
+        .languages
+        .iter()
+        .find(|i| i.name == message.submission.language);
+    match (problem, language) {
+        (Some(p), Some(l)) => {
+            execute_input(
+                message,
+                web::Data::new(pool.clone()),
+                p.clone(),
+                l.clone(),
+                cancel,
+            );
+        }
+        //the problem or language was removed from the config
+        _ => fail_job(pool, message.id),
+    }
+}
+//finish a Running job with System Error
+fn fail_job(pool: &Pool<SqliteConnectionManager>, id: i32) {
+    let _ = pool.get().map(|conn| {
+        conn.execute(
+            "UPDATE task SET state=?1,result=?2,updated_time=?3 WHERE id=?4 AND state='Running'",
+            params![
+                State::Finished.to_string(),
+                EnumResult::SystemError.to_string(),
+                Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+                id
+            ],
+        )
+    });
+}
+//put a Finished job back into the queue,false if it is not finished
+pub fn requeue_job(
+    pool: &Pool<SqliteConnectionManager>,
+    message: &mut Message,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if let State::Finished = message.state {
+    } else {
+        return Ok(false);
+    }
+    message.state = State::Queueing;
+    message.result = EnumResult::Waiting;
+    message.score = 0.0;
+    message.updated_time = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    for i in &mut message.cases {
+        i.result = EnumResult::Waiting;
+        i.time = 0;
+        i.memory = 0;
+        i.info = String::new();
+        i.score_ratio = 0.0;
+    }
+    for i in &mut message.subtasks {
+        i.result = EnumResult::Waiting;
+        i.score = 0.0;
+    }
+    let updated = pool.get()?.execute(
+        "UPDATE task SET updated_time=?1,state=?2,result=?3,score=?4,cases=?5,subtasks=?7,diagnostics='[]' WHERE id=?6 AND state='Finished'",
+        params![
+            message.updated_time,
+            message.state.to_string(),
+            message.result.to_string(),
+            message.score,
+            serde_json::to_string(&message.cases)?,
+            message.id,
+            serde_json::to_string(&message.subtasks)?
+        ],
+    )?;
+    Ok(updated == 1)
+}
+//put the jobs left unfinished by the last run of the server back into the queue
+pub fn recover_jobs(
+    pool: &Pool<SqliteConnectionManager>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare("SELECT * FROM task WHERE state=?1 OR state=?2 ORDER BY id")?;
+    let messages = stmt
+        .query_map(
+            params![State::Queueing.to_string(), State::Running.to_string()],
+            message_from_row,
+        )?
+        .collect::<Result<Vec<Message>, rusqlite::Error>>()?;
+    let count = messages.len();
+    for mut message in messages {
+        for i in &mut message.cases {
+            i.result = EnumResult::Waiting;
+            i.time = 0;
+            i.memory = 0;
+            i.info = String::new();
+            i.score_ratio = 0.0;
+        }
+        for i in &mut message.subtasks {
+            i.result = EnumResult::Waiting;
+            i.score = 0.0;
+        }
+        //updated_time is kept,so the jobs keep their place in the queue
+        conn.execute(
+            "UPDATE task SET state=?1,result=?2,score=?3,cases=?4,subtasks=?6,diagnostics='[]' WHERE id=?5",
+            params![
+                State::Queueing.to_string(),
+                EnumResult::Waiting.to_string(),
+                0.0,
+                serde_json::to_string(&message.cases)?,
+                message.id,
+                serde_json::to_string(&message.subtasks)?
+            ],
+        )?;
+    }
+    Ok(count)
+}
+//start the judge workers,at most workers jobs are judged at the same time
+pub fn start_judge_workers(
+    workers: usize,
+    config: Configure,
+    pool: Pool<SqliteConnectionManager>,
+) -> Arc<JudgeQueue> {
+    let queue = Arc::new(JudgeQueue {
+        generation: Mutex::new(0),
+        condvar: Condvar::new(),
+        running: Mutex::new(HashMap::new()),
+        slots: Mutex::new(workers),
+        slot_condvar: Condvar::new(),
+    });
+    for i in 0..workers {
+        let queue = queue.clone();
+        let config = config.clone();
+        let pool = pool.clone();
+        std::thread::Builder::new()
+            .name(format!("judge-worker-{}", i))
+            .spawn(move || loop {
+                let generation = queue.generation();
+                let slot = queue.slot();
+                match queue.claim(&pool) {
+                    Ok(Some((message, cancel))) => {
+                        let id = message.id;
+                        //a panic while judging must neither end the worker nor leave the job Running
+                        let judged = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                            judge(message, &cancel, &config, &pool)
+                        }));
+                        if judged.is_err() {
+                            log::error!("judging job {} panicked", id);
+                            let _ = std::fs::remove_dir_all(format!("temp{}", id));
+                            fail_job(&pool, id);
+                        }
+                        queue.running.lock().unwrap().remove(&id);
+                    }
+                    Ok(None) => {
+                        drop(slot);
+                        queue.wait(generation);
+                    }
+                    Err(r) => {
+                        drop(slot);
+                        log::error!("judge worker failed to fetch a job: {}", r);
+                        std::thread::sleep(Duration::from_secs(1));
+                    }
+                }
+            })
+            .unwrap();
+    }
+    queue
+}
@@ -6,25 +6,38 @@ mod enumresult;
 use enumresult::EnumResult;
 use enumresult::State;
 mod configure;
-use actix_web::rt::spawn;
-use actix_web::web::block;
 use actix_web::{
-    get, middleware::Logger, post, put, web, App, HttpResponse, HttpServer, Responder,
+    delete, get, middleware::Logger, post, put, web, App, HttpResponse, HttpServer, Responder,
     ResponseError,
 };
 use chrono::prelude::*;
 use clap::Parser;
-use configure::{get_configure, Configure};
+use configure::{get_configure, Configure, ProblemType};
 use env_logger;
 use log;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, Result};
+use rusqlite::{params, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::io::{Error, ErrorKind};
+mod checker;
+mod compare;
+mod compile_cache;
+use checker::compile_checkers;
+use generator::generate_data;
 mod execute;
-use execute::{execute_input, CaseResult, Message, Submit};
+mod generator;
+mod sandbox;
+mod subtask;
+use subtask::check_subtasks;
+mod validator;
+mod verify;
+use execute::{message_from_row, new_message, run_custom, CaseResult, CustomRun, Message, Submit};
+use validator::validate_problem;
+use verify::verify_problem;
+mod judge_queue;
+use judge_queue::{recover_jobs, requeue_job, start_judge_workers, JudgeQueue};
 mod rank_sort;
 use rank_sort::{
     sort_by_standard, RankRule, RanklistEntry, RanklistReturn, ScoringRule, ScoringRuleStandard,
@@ -72,13 +85,13 @@ async fn post_jobs(
     body: web::Json<Submit>,
     config: web::Data<Configure>,
     mut pool: web::Data<Pool<SqliteConnectionManager>>,
+    queue: web::Data<JudgeQueue>,
 ) -> Result<impl Responder, impl ResponseError> {
     //check if in config
     let created_time = Utc::now();
     let mut is_language = false;
     let mut is_problem = false;
     let mut problem_index = 0;
-    let mut language_index = 0;
     for i in &config.problems {
         if i.id == body.problem_id {
             is_problem = true;
@@ -91,7 +104,6 @@ async fn post_jobs(
             is_language = true;
             break;
         }
-        language_index += 1;
     }
     if is_problem == false {
         return Ok::<HttpResponse, ErrorMessage>(HttpResponse::NotFound().json(ErrorMessage {
@@ -231,17 +243,6 @@ async fn post_jobs(
             message: "exceed submission limit".to_string(),
         }));
     }
-    //create temporary directory
-    let mut vec_cases = Vec::new();
-    for i in 0..config.problems[problem_index].cases.len() + 1 {
-        vec_cases.push(CaseResult {
-            id: i as i32,
-            result: EnumResult::Waiting,
-            time: 0,
-            memory: 0,
-            info: String::from(""),
-        });
-    }
     //determine job id
     let mut id = 0;
     pool = pool.clone();
@@ -259,27 +260,23 @@ async fn post_jobs(
             .map_err(ErrorMessage::rusqlite_error)?;
         id = (conn as i32) + 1;
     }
-    let message = Message {
-        id: id,
-        created_time: created_time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
-        updated_time: created_time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
-        submission: Submit {
+    let message = new_message(
+        id,
+        created_time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        Submit {
             source_code: body.source_code.clone(),
             language: body.language.clone(),
             user_id: body.user_id,
             contest_id: body.contest_id,
             problem_id: body.problem_id,
         },
-        state: State::Queueing,
-        result: EnumResult::Waiting,
-        score: 0.0,
-        cases: vec_cases,
-    };
+        &config.problems[problem_index],
+    );
     let return_message = message.clone();
     //insert entry in task table
     pool = pool.clone();
     let _ = pool.get().map_err(ErrorMessage::r2error)?.execute(
-        "INSERT INTO task VALUES(?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
+        "INSERT INTO task VALUES(?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,'[]')",
         (
             &message.id,
             &message.submission.user_id,
@@ -293,24 +290,18 @@ async fn post_jobs(
             &message.submission.source_code,
             &message.score,
             &serde_json::to_string(&message.cases)?,
+            &serde_json::to_string(&message.subtasks)?,
         ),
     );
-    //execute program
-    let language = config.languages[language_index].clone();
-    let problem = config.problems[problem_index].clone();
-    let _detached = spawn(async {
-        block(move || {
-            execute_input(message, pool.clone(), problem, language);
-        })
-        .await
-    });
+    //hand over to the judge workers
+    queue.notify();
     return Ok(HttpResponse::Ok().json(return_message));
 }
-#[get("/jobs")]
-async fn get_jobs(
-    info: web::Query<JobQuery>,
+//jobs matching the filters,sorted by created_time
+fn select_jobs(
+    info: &JobQuery,
     mut pool: web::Data<Pool<SqliteConnectionManager>>,
-) -> impl Responder {
+) -> Vec<Message> {
     //create query-string
     let mut query_str = String::new();
     if let Some(s) = info.user_id {
@@ -328,8 +319,7 @@ async fn get_jobs(
             )
             .unwrap();
         if conn == 0 {
-            let v: Vec<Message> = Vec::new();
-            return HttpResponse::Ok().json(v);
+            return Vec::new();
         }
         let conn: usize = pool
             .get()
@@ -342,8 +332,7 @@ async fn get_jobs(
             .unwrap();
         if let Some(id) = info.user_id {
             if id as usize != conn {
-                let v: Vec<Message> = Vec::new();
-                return HttpResponse::Ok().json(v);
+                return Vec::new();
             }
         } else {
             if query_str.len() > 1 {
@@ -392,29 +381,7 @@ async fn get_jobs(
     let mut t = conn
         .prepare(&format!("SELECT * FROM task{}", query_str))
         .unwrap();
-    let tasks_iter = t
-        .query_map([], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                created_time: row.get(5)?,
-                updated_time: row.get(8)?,
-                submission: Submit {
-                    source_code: row.get(9)?,
-                    language: row.get(4)?,
-                    user_id: row.get(1)?,
-                    contest_id: row.get(2)?,
-                    problem_id: row.get(3)?,
-                },
-                state: State::state_from_string(row.get(6).unwrap()),
-                result: EnumResult::enumresult_from_string(row.get(7).unwrap()),
-                score: row.get(10)?,
-                cases: {
-                    let t: String = row.get(11)?;
-                    serde_json::from_str(&t).unwrap()
-                },
-            })
-        })
-        .unwrap();
+    let tasks_iter = t.query_map([], message_from_row).unwrap();
     let mut vec_select = Vec::new();
     for i in tasks_iter {
         vec_select.push(i.unwrap());
@@ -467,7 +434,49 @@ async fn get_jobs(
         let duration = ta.signed_duration_since(tb);
         duration.cmp(&chrono::Duration::zero())
     });
-    return HttpResponse::Ok().json(vec_select);
+    vec_select
+}
+#[get("/jobs")]
+async fn get_jobs(
+    info: web::Query<JobQuery>,
+    pool: web::Data<Pool<SqliteConnectionManager>>,
+) -> impl Responder {
+    HttpResponse::Ok().json(select_jobs(&info, pool))
+}
+#[derive(Serialize, Deserialize, Debug)]
+struct RejudgeSummary {
+    count: usize,
+    //ids of the requeued jobs
+    jobs: Vec<i32>,
+    //ids of matching jobs that were not finished
+    skipped: Vec<i32>,
+}
+#[post("/jobs/rejudge")]
+async fn rejudge_jobs(
+    info: web::Query<JobQuery>,
+    pool: web::Data<Pool<SqliteConnectionManager>>,
+    queue: web::Data<JudgeQueue>,
+) -> Result<impl Responder, impl ResponseError> {
+    let mut summary = RejudgeSummary {
+        count: 0,
+        jobs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for mut message in select_jobs(&info, pool.clone()) {
+        let is_requeued = requeue_job(&pool, &mut message).map_err(|r| ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        })?;
+        if is_requeued {
+            summary.jobs.push(message.id);
+        } else {
+            summary.skipped.push(message.id);
+        }
+    }
+    summary.count = summary.jobs.len();
+    queue.notify();
+    Ok::<HttpResponse, ErrorMessage>(HttpResponse::Ok().json(summary))
 }
 #[get("/jobs/{id}")]
 async fn gets_job_id(
@@ -477,29 +486,7 @@ async fn gets_job_id(
     pool = pool.clone();
     let conn = pool.get().unwrap();
     let mut t = conn.prepare("SELECT * FROM task WHERE id=?1").unwrap();
-    let tasks_iter = t
-        .query_map([*id], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                created_time: row.get(5)?,
-                updated_time: row.get(8)?,
-                submission: Submit {
-                    source_code: row.get(9)?,
-                    language: row.get(4)?,
-                    user_id: row.get(1)?,
-                    contest_id: row.get(2)?,
-                    problem_id: row.get(3)?,
-                },
-                state: State::state_from_string(row.get(6).unwrap()),
-                result: EnumResult::enumresult_from_string(row.get(7).unwrap()),
-                score: row.get(10)?,
-                cases: {
-                    let t: String = row.get(11)?;
-                    serde_json::from_str(&t).unwrap()
-                },
-            })
-        })
-        .unwrap();
+    let tasks_iter = t.query_map([*id], message_from_row).unwrap();
     let mut vec_select = Vec::new();
     for i in tasks_iter {
         vec_select.push(i.unwrap());
@@ -513,11 +500,42 @@ async fn gets_job_id(
     }
     return HttpResponse::Ok().json(vec_select.pop().unwrap());
 }
+//the job with where each wrong output differs from the answer,for admins
+#[get("/internal/jobs/{id}")]
+async fn get_internal_job_id(
+    id: web::Path<i32>,
+    pool: web::Data<Pool<SqliteConnectionManager>>,
+) -> impl Responder {
+    let conn = pool.get().unwrap();
+    let row = conn
+        .query_row("SELECT * FROM task WHERE id=?1", [*id], |row| {
+            Ok((message_from_row(row)?, row.get::<_, String>("diagnostics")?))
+        })
+        .optional()
+        .unwrap();
+    let (mut message, diagnostics) = match row {
+        Some(r) => r,
+        None => {
+            return HttpResponse::NotFound().json(ErrorMessage {
+                code: 3,
+                reason: ErrorReason::ErrNotFound,
+                message: format!("Job {} not found.", id),
+            });
+        }
+    };
+    let diagnostics: Vec<String> = serde_json::from_str(&diagnostics).unwrap_or_default();
+    for (case, difference) in message.cases.iter_mut().zip(diagnostics) {
+        if !difference.is_empty() {
+            case.info = difference;
+        }
+    }
+    HttpResponse::Ok().json(message)
+}
 #[put("/jobs/{id}")]
 async fn put_jobs(
     id: web::Path<i32>,
     mut pool: web::Data<Pool<SqliteConnectionManager>>,
-    config: web::Data<Configure>,
+    queue: web::Data<JudgeQueue>,
 ) -> impl Responder {
     pool = pool.clone();
     //get message
@@ -525,30 +543,7 @@ async fn put_jobs(
     let mut t = conn
         .prepare(&format!("SELECT * FROM task WHERE id={}", id))
         .unwrap();
-    let tasks_iter = t
-        .query_map([], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                created_time: row.get(5)?,
-                updated_time: row.get(8)?,
-                submission: Submit {
-                    source_code: row.get(9)?,
-                    language: row.get(4)?,
-                    user_id: row.get(1)?,
-                    contest_id: row.get(2)?,
-                    problem_id: row.get(3)?,
-                },
-                state: State::state_from_string(row.get(6).unwrap()),
-                result: EnumResult::enumresult_from_string(row.get(7).unwrap()),
-                score: row.get(10)?,
-                cases: {
-                    let t: String = row.get(11)?;
-                    let vec_cases: Vec<CaseResult> = serde_json::from_str(&t).unwrap();
-                    vec_cases
-                },
-            })
-        })
-        .unwrap();
+    let tasks_iter = t.query_map([], message_from_row).unwrap();
     let mut task_vec = Vec::new();
     for i in tasks_iter {
         task_vec.push(i.unwrap());
@@ -569,65 +564,155 @@ async fn put_jobs(
             message: format!("Job {} not finished.", id),
         });
     } //retest
-      //get language
-    let mut language_index = 0;
-    for i in &config.languages {
-        if i.name == message.submission.language {
-            break;
-        }
-        language_index += 1;
+    if let Err(r) = requeue_job(&pool, &mut message) {
+        return HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        });
     }
-    let language = config.languages[language_index as usize].clone();
-    //get problem
-    let mut problem = config.problems[0].clone();
-    for i in &config.problems {
-        if i.id == message.submission.problem_id {
-            problem = i.clone();
+    queue.notify();
+    return HttpResponse::Ok().json(message);
+}
+
+//run the validator of the problem on all its input files
+#[post("/internal/problems/{id}/validate")]
+async fn validate_problem_data(id: web::Path<i32>, config: web::Data<Configure>) -> impl Responder {
+    let problem = match config.problems.iter().find(|i| i.id == *id) {
+        Some(r) => r.clone(),
+        None => {
+            return HttpResponse::NotFound().json(ErrorMessage {
+                code: 3,
+                reason: ErrorReason::ErrNotFound,
+                message: format!("Problem {} not found.", id),
+            });
         }
+    };
+    match web::block(move || validate_problem(&problem)).await {
+        Ok(Some(r)) => HttpResponse::Ok().json(r),
+        Ok(None) => HttpResponse::BadRequest().json(ErrorMessage {
+            code: 1,
+            reason: ErrorReason::ErrInvalidArgument,
+            message: format!("Problem {} has no validator.", id),
+        }),
+        Err(r) => HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        }),
     }
-    //generate message return,update message
-    let mut message_return = message.clone();
-    message_return.result = EnumResult::Waiting;
-    message_return.score = 0.0;
-    message_return.state = State::Queueing;
-    message_return.updated_time = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-    for i in &mut message_return.cases {
-        i.result = EnumResult::Waiting;
-        i.time = 0;
-        i.memory = 0;
-        i.info = String::new();
+}
+//judge the solutions listed by the problem,reporting those that get an unexpected result
+#[post("/internal/problems/{id}/verify")]
+async fn verify_problem_solutions(
+    id: web::Path<i32>,
+    config: web::Data<Configure>,
+    pool: web::Data<Pool<SqliteConnectionManager>>,
+    queue: web::Data<JudgeQueue>,
+) -> impl Responder {
+    let problem = match config.problems.iter().find(|i| i.id == *id) {
+        Some(r) => r.clone(),
+        None => {
+            return HttpResponse::NotFound().json(ErrorMessage {
+                code: 3,
+                reason: ErrorReason::ErrNotFound,
+                message: format!("Problem {} not found.", id),
+            });
+        }
+    };
+    let languages = config.languages.clone();
+    let result = web::block(move || {
+        let _slot = queue.slot();
+        verify_problem(&problem, &languages, &pool)
+    })
+    .await;
+    match result {
+        Ok(Some(r)) => HttpResponse::Ok().json(r),
+        Ok(None) => HttpResponse::BadRequest().json(ErrorMessage {
+            code: 1,
+            reason: ErrorReason::ErrInvalidArgument,
+            message: format!("Problem {} has no solutions to verify.", id),
+        }),
+        Err(r) => HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        }),
     }
-    message.result = EnumResult::Waiting;
-    message.score = 0.0;
-    message.state = State::Queueing;
-    message.updated_time = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-    for i in &mut message.cases {
-        i.result = EnumResult::Waiting;
-        i.time = 0;
-        i.memory = 0;
-        i.info = String::new();
+}
+//run the source on custom input without creating a job or counting towards submission limits
+#[post("/runs")]
+async fn post_runs(
+    body: web::Json<CustomRun>,
+    config: web::Data<Configure>,
+    queue: web::Data<JudgeQueue>,
+) -> impl Responder {
+    let problem = match config.problems.iter().find(|i| i.id == body.problem_id) {
+        Some(r) => r.clone(),
+        None => {
+            return HttpResponse::NotFound().json(ErrorMessage {
+                code: 3,
+                reason: ErrorReason::ErrNotFound,
+                message: format!("Problem {} not found.", body.problem_id),
+            });
+        }
+    };
+    let language = match config.languages.iter().find(|i| i.name == body.language) {
+        Some(r) => r.clone(),
+        None => {
+            return HttpResponse::NotFound().json(ErrorMessage {
+                code: 3,
+                reason: ErrorReason::ErrNotFound,
+                message: format!("Language {} not found.", body.language),
+            });
+        }
+    };
+    let run = body.into_inner();
+    let result = web::block(move || {
+        let _slot = queue.slot();
+        run_custom(&run, &problem, &language).map_err(|r| r.to_string())
+    })
+    .await;
+    match result {
+        Ok(Ok(r)) => HttpResponse::Ok().json(r),
+        Ok(Err(r)) => HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r,
+        }),
+        Err(r) => HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        }),
     }
-    let _ = pool.get().unwrap().execute(
-        "UPDATE tasks SET updated_time=?1,state=?2,result=?3,score=?4,cases=?5 WHERE id=?6",
-        params![
-            &message.updated_time.clone(),
-            message.state.to_string(),
-            message.result.to_string(),
-            &message.score,
-            &serde_json::to_string(&message.cases).unwrap(),
-            &message.id
-        ],
-    );
-    //update sql
-    let _detached = spawn(async {
-        block(move || {
-            execute_input(message, pool.clone(), problem, language);
-        })
-        .await;
-    });
-    return HttpResponse::Ok().json(message_return);
 }
 
+#[delete("/jobs/{id}")]
+async fn delete_jobs(
+    id: web::Path<i32>,
+    pool: web::Data<Pool<SqliteConnectionManager>>,
+    queue: web::Data<JudgeQueue>,
+) -> impl Responder {
+    match queue.cancel(&pool, *id) {
+        Err(r) => HttpResponse::InternalServerError().json(ErrorMessage {
+            code: 6,
+            reason: ErrorReason::ErrInternal,
+            message: r.to_string(),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(ErrorMessage {
+            code: 3,
+            reason: ErrorReason::ErrNotFound,
+            message: format!("Job {} not found.", id),
+        }),
+        Ok(Some((_message, false))) => HttpResponse::BadRequest().json(ErrorMessage {
+            code: 2,
+            reason: ErrorReason::ErrInvalidState,
+            message: format!("Job {} not queueing or running.", id),
+        }),
+        Ok(Some((message, true))) => HttpResponse::Ok().json(message),
+    }
+}
 #[derive(Serialize, Deserialize, Debug)]
 struct InputUser {
     id: Option<i32>,
@@ -836,16 +921,24 @@ async fn get_contest_ranklist(
         for prob_id in &vec_problem_id {
             let c1 = pool.get().unwrap();
             let mut conn = c1
-                .prepare("SELECT created_time,score FROM task WHERE user_id=?1 AND problem_id=?2")
+                .prepare("SELECT created_time,score,result,cases FROM task WHERE user_id=?1 AND problem_id=?2")
                 .unwrap();
             let standard_iter = conn
                 .query_map([user.user.id, *prob_id], |row| {
                     let s: String = row.get(0)?;
+                    let result: String = row.get(2)?;
+                    let cases: String = row.get(3)?;
+                    let cases: Vec<CaseResult> = serde_json::from_str(&cases).unwrap_or_default();
                     Ok(ScoringRuleStandard {
                         submit_time: Some(
                             Utc.datetime_from_str(&s, "%Y-%m-%dT%H:%M:%S%.3fZ").unwrap(),
                         ),
                         score: row.get(1)?,
+                        case_times: if result == EnumResult::Accepted.to_string() {
+                            cases.iter().skip(1).map(|i| i.time).collect()
+                        } else {
+                            Vec::new()
+                        },
                     })
                 })
                 .unwrap();
@@ -858,6 +951,7 @@ async fn get_contest_ranklist(
                 user.scores.push(ScoringRuleStandard {
                     submit_time: None,
                     score: (0.0),
+                    case_times: Vec::new(),
                 });
             } else {
                 //at least one submit
@@ -910,7 +1004,38 @@ async fn get_contest_ranklist(
                 }
             }
         }
-        //update final score
+    }
+    //dynamic ranking gives the rest of each case score of an Accepted submission by its time
+    //against the least time of that case among the counted submissions
+    for (index, prob_id) in vec_problem_id.iter().enumerate() {
+        let problem = match config.problems.iter().find(|i| i.id == *prob_id) {
+            Some(r) => r,
+            None => continue,
+        };
+        let ratio = match (&problem.ty, &problem.misc) {
+            (ProblemType::DynamicRanking, Some(r)) => r.dynamic_ranking_ratio.unwrap_or(0.0),
+            _ => continue,
+        };
+        for (case_index, case) in problem.cases.iter().enumerate() {
+            let min_time = vec_ranklist
+                .iter()
+                .filter_map(|i| i.scores[index].case_times.get(case_index))
+                .min()
+                .copied();
+            let min_time = match min_time {
+                Some(r) => r.max(1),
+                None => continue,
+            };
+            for user in &mut vec_ranklist {
+                if let Some(time) = user.scores[index].case_times.get(case_index) {
+                    user.scores[index].score +=
+                        case.score * ratio * min_time as f64 / (*time).max(1) as f64;
+                }
+            }
+        }
+    }
+    //update final score
+    for user in &mut vec_ranklist {
         for i in &user.scores {
             user.final_score += i.score;
         }
@@ -1314,7 +1439,7 @@ async fn main() -> std::io::Result<()> {
         }
     }
     let cli = Cli::parse();
-    let config: Configure;
+    let mut config: Configure;
     match cli.config {
         None => {
             return Err(Error::new(
@@ -1326,6 +1451,34 @@ async fn main() -> std::io::Result<()> {
             config = get_configure(r);
         }
     }
+    let checked = check_subtasks(&config)
+        .and_then(|_| compile_checkers(&mut config))
+        .and_then(|_| generate_data(&mut config));
+    if let Err(r) = checked {
+        log::error!("Invalid config: {}", r);
+        return Err(Error::other("invalid config"));
+    }
+    compile_cache::set_cache_size(config.server.compile_cache_size);
+    if config.server.validate_data == Some(true) {
+        config
+            .problems
+            .retain(|problem| match validate_problem(problem) {
+                Some(report) if !report.valid => {
+                    for i in report.cases.iter().filter(|i| !i.valid) {
+                        log::error!(
+                            "Problem {} case {} ({}) is invalid: {}",
+                            problem.id,
+                            i.id,
+                            i.input_file,
+                            i.info
+                        );
+                    }
+                    log::error!("Problem {} is not served", problem.id);
+                    false
+                }
+                _ => true,
+            });
+    }
     //init sql
     let server_address = match &config.server.bind_address {
         Some(r) => r.clone(),
@@ -1357,12 +1510,38 @@ async fn main() -> std::io::Result<()> {
         updated_time TEXT NOT NULL,
         source_code TEXT NOT NULL,
         score REAL,
-        cases TEXT NOT NULL
+        cases TEXT NOT NULL,
+        subtasks TEXT NOT NULL DEFAULT '[]',
+        diagnostics TEXT NOT NULL DEFAULT '[]'
     )
     ",
             params![],
         )
         .unwrap();
+    //databases created by older versions lack the newer columns
+    for column in ["subtasks", "diagnostics"] {
+        let has_column: usize = pool
+            .get()
+            .unwrap()
+            .query_row(
+                "SELECT count(*) FROM pragma_table_info('task') WHERE name=?1",
+                [column],
+                |row| row.get(0),
+            )
+            .unwrap();
+        if has_column == 0 {
+            pool.get()
+                .unwrap()
+                .execute(
+                    &format!(
+                        "ALTER TABLE task ADD COLUMN {} TEXT NOT NULL DEFAULT '[]'",
+                        column
+                    ),
+                    params![],
+                )
+                .unwrap();
+        }
+    }
     pool = pool.clone();
     pool.get()
         .unwrap()
@@ -1407,18 +1586,36 @@ async fn main() -> std::io::Result<()> {
         )
         .unwrap();
     create_contest0(&config, pool.clone());
+    match recover_jobs(&pool) {
+        Ok(0) => {}
+        Ok(r) => log::info!("Requeued {} unfinished jobs", r),
+        Err(r) => log::error!("Failed to requeue unfinished jobs: {}", r),
+    }
+    //default to one judge worker per cpu
+    let workers = match config.server.judge_workers {
+        Some(r) => r.max(1),
+        None => std::thread::available_parallelism().map_or(1, |i| i.get()),
+    };
+    let queue = web::Data::from(start_judge_workers(workers, config.clone(), pool.clone()));
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(config.clone()))
             .app_data(web::Data::new(pool.clone()))
+            .app_data(queue.clone())
             .wrap(Logger::default())
             .route("/hello", web::get().to(|| async { "Hello World!" }))
             .service(greet)
             // DO NOT REMOVE: used in automatic testing
             .service(post_jobs)
             .service(get_jobs)
+            .service(rejudge_jobs)
             .service(gets_job_id)
+            .service(get_internal_job_id)
             .service(put_jobs)
+            .service(delete_jobs)
+            .service(post_runs)
+            .service(validate_problem_data)
+            .service(verify_problem_solutions)
             .service(post_users)
             .service(get_users)
             .service(post_contest)
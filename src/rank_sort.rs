@@ -20,6 +20,8 @@ pub enum ScoringRule {
 pub struct ScoringRuleStandard {
     pub submit_time: Option<DateTime<Utc>>,
     pub score: f64,
+    //time of each case of an Accepted submission,which dynamic ranking compares among users
+    pub case_times: Vec<i32>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -0,0 +1,566 @@
+use super::configure::Sandbox;
+use std::ffi::CString;
+use std::io::{Error, ErrorKind};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+//syscalls a program run with sandbox may make,the others fail with ENOSYS
+const ALLOWED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_read,
+    libc::SYS_write,
+    libc::SYS_readv,
+    libc::SYS_writev,
+    libc::SYS_pread64,
+    libc::SYS_pwrite64,
+    libc::SYS_preadv,
+    libc::SYS_pwritev,
+    libc::SYS_openat,
+    libc::SYS_close,
+    libc::SYS_close_range,
+    libc::SYS_lseek,
+    libc::SYS_fstat,
+    libc::SYS_newfstatat,
+    libc::SYS_statx,
+    libc::SYS_statfs,
+    libc::SYS_fstatfs,
+    libc::SYS_readlinkat,
+    libc::SYS_faccessat,
+    libc::SYS_faccessat2,
+    libc::SYS_getdents64,
+    libc::SYS_getcwd,
+    libc::SYS_chdir,
+    libc::SYS_fchdir,
+    libc::SYS_mkdirat,
+    libc::SYS_unlinkat,
+    libc::SYS_renameat,
+    libc::SYS_renameat2,
+    libc::SYS_linkat,
+    libc::SYS_symlinkat,
+    libc::SYS_fchmod,
+    libc::SYS_fchmodat,
+    libc::SYS_utimensat,
+    libc::SYS_umask,
+    libc::SYS_truncate,
+    libc::SYS_ftruncate,
+    libc::SYS_fsync,
+    libc::SYS_fdatasync,
+    libc::SYS_fadvise64,
+    libc::SYS_fcntl,
+    libc::SYS_flock,
+    libc::SYS_ioctl,
+    libc::SYS_dup,
+    libc::SYS_dup3,
+    libc::SYS_pipe2,
+    libc::SYS_sendfile,
+    libc::SYS_splice,
+    libc::SYS_tee,
+    libc::SYS_copy_file_range,
+    libc::SYS_ppoll,
+    libc::SYS_pselect6,
+    libc::SYS_epoll_create1,
+    libc::SYS_epoll_ctl,
+    libc::SYS_epoll_pwait,
+    libc::SYS_epoll_pwait2,
+    libc::SYS_eventfd2,
+    libc::SYS_memfd_create,
+    libc::SYS_mmap,
+    libc::SYS_munmap,
+    libc::SYS_mremap,
+    libc::SYS_mprotect,
+    libc::SYS_madvise,
+    libc::SYS_mincore,
+    libc::SYS_msync,
+    libc::SYS_mlock,
+    libc::SYS_munlock,
+    libc::SYS_brk,
+    libc::SYS_membarrier,
+    libc::SYS_rt_sigaction,
+    libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn,
+    libc::SYS_rt_sigpending,
+    libc::SYS_rt_sigtimedwait,
+    libc::SYS_rt_sigsuspend,
+    libc::SYS_sigaltstack,
+    libc::SYS_kill,
+    libc::SYS_tkill,
+    libc::SYS_tgkill,
+    libc::SYS_clone,
+    libc::SYS_clone3,
+    libc::SYS_execve,
+    libc::SYS_wait4,
+    libc::SYS_waitid,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+    libc::SYS_set_tid_address,
+    libc::SYS_set_robust_list,
+    libc::SYS_get_robust_list,
+    libc::SYS_rseq,
+    libc::SYS_futex,
+    libc::SYS_restart_syscall,
+    libc::SYS_sched_yield,
+    libc::SYS_sched_getaffinity,
+    libc::SYS_sched_getparam,
+    libc::SYS_sched_getscheduler,
+    libc::SYS_sched_get_priority_max,
+    libc::SYS_sched_get_priority_min,
+    libc::SYS_getcpu,
+    libc::SYS_nanosleep,
+    libc::SYS_clock_nanosleep,
+    libc::SYS_clock_gettime,
+    libc::SYS_clock_getres,
+    libc::SYS_gettimeofday,
+    libc::SYS_getitimer,
+    libc::SYS_setitimer,
+    libc::SYS_timer_create,
+    libc::SYS_timer_settime,
+    libc::SYS_timer_gettime,
+    libc::SYS_timer_getoverrun,
+    libc::SYS_timer_delete,
+    libc::SYS_timerfd_create,
+    libc::SYS_timerfd_settime,
+    libc::SYS_timerfd_gettime,
+    libc::SYS_times,
+    libc::SYS_getrusage,
+    libc::SYS_getrlimit,
+    libc::SYS_setrlimit,
+    libc::SYS_prlimit64,
+    libc::SYS_sysinfo,
+    libc::SYS_uname,
+    libc::SYS_getrandom,
+    libc::SYS_prctl,
+    libc::SYS_getpid,
+    libc::SYS_getppid,
+    libc::SYS_gettid,
+    libc::SYS_getuid,
+    libc::SYS_geteuid,
+    libc::SYS_getgid,
+    libc::SYS_getegid,
+    libc::SYS_getresuid,
+    libc::SYS_getresgid,
+    libc::SYS_getgroups,
+    libc::SYS_getpgid,
+    libc::SYS_setpgid,
+    libc::SYS_getsid,
+    libc::SYS_setsid,
+    libc::SYS_getpriority,
+    libc::SYS_capget,
+];
+//older syscalls that only some architectures have
+#[cfg(target_arch = "x86_64")]
+const ALLOWED_LEGACY_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_open,
+    libc::SYS_creat,
+    libc::SYS_stat,
+    libc::SYS_lstat,
+    libc::SYS_access,
+    libc::SYS_readlink,
+    libc::SYS_getdents,
+    libc::SYS_mkdir,
+    libc::SYS_rmdir,
+    libc::SYS_unlink,
+    libc::SYS_rename,
+    libc::SYS_link,
+    libc::SYS_symlink,
+    libc::SYS_chmod,
+    libc::SYS_dup2,
+    libc::SYS_pipe,
+    libc::SYS_poll,
+    libc::SYS_select,
+    libc::SYS_epoll_create,
+    libc::SYS_epoll_wait,
+    libc::SYS_eventfd,
+    libc::SYS_fork,
+    libc::SYS_vfork,
+    libc::SYS_arch_prctl,
+    libc::SYS_time,
+    libc::SYS_alarm,
+    libc::SYS_pause,
+    libc::SYS_getpgrp,
+];
+#[cfg(target_arch = "aarch64")]
+const ALLOWED_LEGACY_SYSCALLS: &[libc::c_long] = &[];
+//syscalls outside ALLOWED_SYSCALLS that allowed_syscalls of the language may name
+const OPTIONAL_SYSCALLS: &[(&str, libc::c_long)] = &[
+    ("ptrace", libc::SYS_ptrace),
+    ("socket", libc::SYS_socket),
+    ("socketpair", libc::SYS_socketpair),
+    ("bpf", libc::SYS_bpf),
+    ("perf_event_open", libc::SYS_perf_event_open),
+    ("io_uring_setup", libc::SYS_io_uring_setup),
+    ("io_uring_enter", libc::SYS_io_uring_enter),
+    ("io_uring_register", libc::SYS_io_uring_register),
+    ("sched_setaffinity", libc::SYS_sched_setaffinity),
+    ("setpriority", libc::SYS_setpriority),
+    ("mbind", libc::SYS_mbind),
+    ("get_mempolicy", libc::SYS_get_mempolicy),
+    ("set_mempolicy", libc::SYS_set_mempolicy),
+];
+//the id of the user inside the sandbox,mapped to the user running the judge or to nobody for root
+const SANDBOX_ID: u32 = 1000;
+const NOBODY_ID: u32 = 65534;
+const DEFAULT_PROCESS_LIMIT: u64 = 64;
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+//not exported by libc yet
+const MOUNT_ATTR_RDONLY: u64 = 0x1;
+const OPEN_TREE_CLONE: libc::c_uint = 0x1;
+const MOVE_MOUNT_F_EMPTY_PATH: libc::c_uint = 0x4;
+const PR_CAPBSET_DROP: libc::c_int = 24;
+const PR_CAP_AMBIENT: libc::c_int = 47;
+const PR_CAP_AMBIENT_CLEAR_ALL: libc::c_ulong = 4;
+//higher than the last capability of any kernel so far,dropping unknown ones fails harmlessly
+const CAP_COUNT: libc::c_ulong = 64;
+#[repr(C)]
+struct MountAttr {
+    attr_set: u64,
+    attr_clr: u64,
+    propagation: u64,
+    userns_fd: u64,
+}
+fn bpf_statement(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+fn bpf_jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+//build the seccomp program:kill on a foreign arch,allow the listed syscalls and fail the others
+fn seccomp_filter(sandbox: &Sandbox) -> Vec<libc::sock_filter> {
+    let mut allowed: Vec<libc::c_long> = ALLOWED_SYSCALLS
+        .iter()
+        .chain(ALLOWED_LEGACY_SYSCALLS)
+        .copied()
+        .collect();
+    for (name, nr) in OPTIONAL_SYSCALLS {
+        let is_allowed = match &sandbox.allowed_syscalls {
+            Some(v) => v.iter().any(|i| i == name),
+            None => false,
+        };
+        if is_allowed {
+            allowed.push(*nr);
+        }
+    }
+    let mut filter = vec![
+        //seccomp_data.arch
+        bpf_statement(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, 4),
+        bpf_jump(
+            libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K,
+            AUDIT_ARCH,
+            1,
+            0,
+        ),
+        bpf_statement(libc::BPF_RET | libc::BPF_K, libc::SECCOMP_RET_KILL_PROCESS),
+        //seccomp_data.nr
+        bpf_statement(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, 0),
+    ];
+    for i in allowed {
+        filter.push(bpf_jump(
+            libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K,
+            i as u32,
+            0,
+            1,
+        ));
+        filter.push(bpf_statement(
+            libc::BPF_RET | libc::BPF_K,
+            libc::SECCOMP_RET_ALLOW,
+        ));
+    }
+    filter.push(bpf_statement(
+        libc::BPF_RET | libc::BPF_K,
+        libc::SECCOMP_RET_ERRNO | (libc::ENOSYS as u32 & libc::SECCOMP_RET_DATA),
+    ));
+    filter
+}
+fn to_cstring(s: &[u8]) -> Result<CString, Error> {
+    CString::new(s).map_err(|_r| Error::from(std::io::ErrorKind::InvalidInput))
+}
+fn check(ret: libc::c_long) -> Result<(), Error> {
+    if ret < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+fn write_proc_file(dir: libc::c_int, path: &CString, content: &CString) -> Result<(), Error> {
+    unsafe {
+        let fd = libc::openat(dir, path.as_ptr(), libc::O_WRONLY);
+        check(fd as libc::c_long)?;
+        let len = content.as_bytes().len();
+        let written = libc::write(fd, content.as_ptr() as *const libc::c_void, len);
+        libc::close(fd);
+        check(written as libc::c_long)
+    }
+}
+unsafe fn wait_status(pid: libc::pid_t) -> libc::c_int {
+    let mut status = 0;
+    while libc::waitpid(pid, &mut status, 0) < 0 {}
+    status
+}
+//end this process the same way as the one that produced status
+unsafe fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let sig = libc::WTERMSIG(status);
+        libc::signal(sig, libc::SIG_DFL);
+        libc::kill(libc::getpid(), sig);
+        libc::_exit(128 + sig);
+    }
+    libc::_exit(libc::WEXITSTATUS(status));
+}
+//keep only fd as stdin,so the stdio files and the exec error pipe of std are not held open
+unsafe fn keep_only(fd: libc::c_int) {
+    libc::dup2(fd, 0);
+    libc::syscall(libc::SYS_close_range, 1, u32::MAX, 0);
+}
+//where folder_name is seen by a program run with sandbox
+pub fn sandbox_folder<'a>(folder_name: &'a str, sandbox: &Option<Sandbox>) -> &'a str {
+    match sandbox {
+        None => folder_name,
+        Some(_) => "/tmp",
+    }
+}
+//run args inside folder_name with the sandbox of the language,or directly if it has none,
+//paths in args must be the ones given by sandbox_folder
+pub fn sandbox_command(
+    folder_name: &str,
+    mut args: Vec<String>,
+    sandbox: &Option<Sandbox>,
+) -> Result<Command, Error> {
+    if args.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "empty run command"));
+    }
+    let program = args.remove(0);
+    let sandbox = match sandbox {
+        None => {
+            let mut command = Command::new(program);
+            command.args(args);
+            return Ok(command);
+        }
+        Some(r) => r.clone(),
+    };
+    let work_dir = std::env::current_dir()?;
+    let scratch_dir = work_dir.join(folder_name);
+    //the program runs as a user other than root in a new user namespace,so it holds no
+    //capability after exec and RLIMIT_NPROC counts the processes of this job alone;root maps
+    //it to nobody,as root itself is exempt from RLIMIT_NPROC
+    let is_root = unsafe { libc::getuid() } == 0;
+    let (uid, gid) = if is_root {
+        //nobody writes into the job folder
+        std::os::unix::fs::chown(&scratch_dir, Some(NOBODY_ID), Some(NOBODY_ID))?;
+        (NOBODY_ID, NOBODY_ID)
+    } else {
+        unsafe { (libc::getuid(), libc::getgid()) }
+    };
+    //prepare everything before fork,the pre_exec closure must not allocate
+    let work_dir = to_cstring(work_dir.as_os_str().as_bytes())?;
+    let scratch_dir = to_cstring(scratch_dir.as_os_str().as_bytes())?;
+    let uid_map = to_cstring(format!("{} {} 1", SANDBOX_ID, uid).as_bytes())?;
+    let gid_map = to_cstring(format!("{} {} 1", SANDBOX_ID, gid).as_bytes())?;
+    let self_dir = to_cstring(b"/proc/self")?;
+    let setgroups_path = to_cstring(b"setgroups")?;
+    let uid_map_path = to_cstring(b"uid_map")?;
+    let gid_map_path = to_cstring(b"gid_map")?;
+    let deny = to_cstring(b"deny")?;
+    let root = to_cstring(b"/")?;
+    let tmp = to_cstring(b"/tmp")?;
+    let proc_dir = to_cstring(b"/proc")?;
+    let proc_type = to_cstring(b"proc")?;
+    let tmpfs_type = to_cstring(b"tmpfs")?;
+    let empty_path = to_cstring(b"")?;
+    let process_limit = sandbox.process_limit.unwrap_or(DEFAULT_PROCESS_LIMIT);
+    let filter = seccomp_filter(&sandbox);
+    let mut command = Command::new(program);
+    command.args(args);
+    unsafe {
+        command.pre_exec(move || {
+            if is_root {
+                check(libc::setgroups(0, std::ptr::null()) as libc::c_long)?;
+            }
+            //only a process outside the new user namespace may map it to nobody,so a mapper
+            //forked beforehand writes the maps once this process has unshared
+            let self_fd = libc::open(
+                self_dir.as_ptr(),
+                libc::O_PATH | libc::O_DIRECTORY | libc::O_CLOEXEC,
+            );
+            check(self_fd as libc::c_long)?;
+            let mut map_pipe = [0; 2];
+            check(libc::pipe2(map_pipe.as_mut_ptr(), libc::O_CLOEXEC) as libc::c_long)?;
+            let mapper = libc::fork();
+            check(mapper as libc::c_long)?;
+            if mapper == 0 {
+                libc::close(map_pipe[1]);
+                let mut unshared = 0u8;
+                if libc::read(
+                    map_pipe[0],
+                    &mut unshared as *mut u8 as *mut libc::c_void,
+                    1,
+                ) != 1
+                {
+                    libc::_exit(1);
+                }
+                let written = write_proc_file(self_fd, &setgroups_path, &deny)
+                    .and_then(|_| write_proc_file(self_fd, &uid_map_path, &uid_map))
+                    .and_then(|_| write_proc_file(self_fd, &gid_map_path, &gid_map));
+                libc::_exit(if written.is_ok() { 0 } else { 1 });
+            }
+            let unshared = check(libc::unshare(
+                libc::CLONE_NEWUSER
+                    | libc::CLONE_NEWNS
+                    | libc::CLONE_NEWNET
+                    | libc::CLONE_NEWPID
+                    | libc::CLONE_NEWIPC
+                    | libc::CLONE_NEWUTS,
+            ) as libc::c_long);
+            if unshared.is_ok() {
+                libc::write(map_pipe[1], [1u8].as_ptr() as *const libc::c_void, 1);
+            }
+            libc::close(map_pipe[0]);
+            libc::close(map_pipe[1]);
+            libc::close(self_fd);
+            let mapped = wait_status(mapper);
+            unshared?;
+            if !libc::WIFEXITED(mapped) || libc::WEXITSTATUS(mapped) != 0 {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    "cannot map the user",
+                ));
+            }
+            //the forked child stays outside the new pid namespace,pid 1 inside only reaps the
+            //submission,since the kernel drops signals like SIGXCPU that pid 1 sends itself
+            let mut status_pipe = [0; 2];
+            check(libc::pipe2(status_pipe.as_mut_ptr(), libc::O_CLOEXEC) as libc::c_long)?;
+            let pid = libc::fork();
+            check(pid as libc::c_long)?;
+            if pid > 0 {
+                keep_only(status_pipe[0]);
+                let status = wait_status(pid);
+                let mut relayed: libc::c_int = 0;
+                let len = std::mem::size_of::<libc::c_int>();
+                if libc::read(
+                    0,
+                    &mut relayed as *mut libc::c_int as *mut libc::c_void,
+                    len,
+                ) == len as isize
+                {
+                    exit_like(relayed);
+                }
+                exit_like(status);
+            }
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+            let null = std::ptr::null();
+            let no_data = std::ptr::null::<libc::c_void>();
+            check(libc::mount(
+                null,
+                root.as_ptr(),
+                null,
+                libc::MS_REC | libc::MS_PRIVATE,
+                no_data,
+            ) as libc::c_long)?;
+            //the job folder becomes the only writable place,the judge's directory is hidden;
+            //take the folder before the tmpfs hides it,work_dir may well be under /tmp
+            let scratch_tree = libc::syscall(
+                libc::SYS_open_tree,
+                libc::AT_FDCWD,
+                scratch_dir.as_ptr(),
+                OPEN_TREE_CLONE | libc::O_CLOEXEC as libc::c_uint,
+            );
+            check(scratch_tree)?;
+            check(libc::mount(
+                tmpfs_type.as_ptr(),
+                work_dir.as_ptr(),
+                tmpfs_type.as_ptr(),
+                0,
+                no_data,
+            ) as libc::c_long)?;
+            check(libc::syscall(
+                libc::SYS_move_mount,
+                scratch_tree,
+                empty_path.as_ptr(),
+                libc::AT_FDCWD,
+                tmp.as_ptr(),
+                MOVE_MOUNT_F_EMPTY_PATH,
+            ))?;
+            libc::close(scratch_tree as libc::c_int);
+            check(libc::mount(
+                proc_type.as_ptr(),
+                proc_dir.as_ptr(),
+                proc_type.as_ptr(),
+                0,
+                no_data,
+            ) as libc::c_long)?;
+            let read_only = MountAttr {
+                attr_set: MOUNT_ATTR_RDONLY,
+                attr_clr: 0,
+                propagation: 0,
+                userns_fd: 0,
+            };
+            check(libc::syscall(
+                libc::SYS_mount_setattr,
+                libc::AT_FDCWD,
+                root.as_ptr(),
+                libc::AT_RECURSIVE,
+                &read_only as *const MountAttr,
+                std::mem::size_of::<MountAttr>(),
+            ))?;
+            let writable = MountAttr {
+                attr_set: 0,
+                attr_clr: MOUNT_ATTR_RDONLY,
+                propagation: 0,
+                userns_fd: 0,
+            };
+            check(libc::syscall(
+                libc::SYS_mount_setattr,
+                libc::AT_FDCWD,
+                tmp.as_ptr(),
+                0,
+                &writable as *const MountAttr,
+                std::mem::size_of::<MountAttr>(),
+            ))?;
+            check(libc::chdir(tmp.as_ptr()) as libc::c_long)?;
+            let pid = libc::fork();
+            check(pid as libc::c_long)?;
+            if pid > 0 {
+                keep_only(status_pipe[1]);
+                let status = wait_status(pid);
+                let len = std::mem::size_of::<libc::c_int>();
+                libc::write(0, &status as *const libc::c_int as *const libc::c_void, len);
+                libc::_exit(0);
+            }
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+            //counted per user within the user namespace
+            let limit = libc::rlimit {
+                rlim_cur: process_limit,
+                rlim_max: process_limit,
+            };
+            check(libc::setrlimit(libc::RLIMIT_NPROC, &limit) as libc::c_long)?;
+            //nothing may give the capabilities back,not even a program run later
+            for i in 0..CAP_COUNT {
+                libc::prctl(PR_CAPBSET_DROP, i);
+            }
+            libc::prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_CLEAR_ALL, 0, 0, 0);
+            check(libc::setresgid(SANDBOX_ID, SANDBOX_ID, SANDBOX_ID) as libc::c_long)?;
+            check(libc::setresuid(SANDBOX_ID, SANDBOX_ID, SANDBOX_ID) as libc::c_long)?;
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) as libc::c_long)?;
+            let program = libc::sock_fprog {
+                len: filter.len() as u16,
+                filter: filter.as_ptr() as *mut libc::sock_filter,
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &program as *const libc::sock_fprog,
+            ) as libc::c_long)?;
+            Ok(())
+        });
+    }
+    Ok(command)
+}
@@ -0,0 +1,191 @@
+use super::configure::{Configure, Problem, Subtask, SubtaskPolicy};
+use super::enumresult::EnumResult;
+use super::execute::CaseResult;
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubtaskResult {
+    pub id: i32,
+    pub result: EnumResult,
+    pub score: f64,
+}
+//subtasks of the problem,each pack of misc.packing is an all_or_nothing subtask
+pub fn problem_subtasks(problem: &Problem) -> Vec<Subtask> {
+    let misc = match &problem.misc {
+        Some(r) => r,
+        None => return Vec::new(),
+    };
+    if let Some(r) = &misc.subtasks {
+        return r.clone();
+    }
+    let mut vec_subtasks = Vec::new();
+    if let Some(r) = &misc.packing {
+        for i in r {
+            vec_subtasks.push(Subtask {
+                score: i.iter().map(|j| problem.cases[*j as usize - 1].score).sum(),
+                policy: Some(SubtaskPolicy::AllOrNothing),
+                cases: i.clone(),
+                dependencies: None,
+            });
+        }
+    }
+    vec_subtasks
+}
+//reject case ids that are not cases of the problem and dependencies on subtasks that do not
+//come earlier,which would panic or silently score nothing while judging
+pub fn check_subtasks(config: &Configure) -> Result<(), String> {
+    for problem in &config.problems {
+        let misc = match &problem.misc {
+            Some(r) => r,
+            None => continue,
+        };
+        let packs = misc.packing.iter().flatten();
+        let subtasks = misc.subtasks.iter().flatten();
+        for cases in packs.chain(subtasks.clone().map(|i| &i.cases)) {
+            let invalid = cases
+                .iter()
+                .find(|i| **i < 1 || **i as usize > problem.cases.len());
+            if let Some(r) = invalid {
+                return Err(format!("problem {} has no case {}", problem.id, r));
+            }
+        }
+        for (index, subtask) in subtasks.enumerate() {
+            if let Some(r) = subtask.dependencies.iter().flatten().find(|i| **i >= index) {
+                return Err(format!(
+                    "subtask {} of problem {} depends on subtask {},which does not come before it",
+                    index, problem.id, r
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+//share of the case score earned by a judged case
+fn case_ratio(case: &CaseResult) -> f64 {
+    match case.result {
+        EnumResult::Accepted => 1.0,
+        EnumResult::PartiallyAccepted => case.score_ratio,
+        _ => 0.0,
+    }
+}
+fn is_judged(case: &CaseResult) -> bool {
+    !matches!(case.result, EnumResult::Waiting | EnumResult::Running)
+}
+//whether a subtask can no longer get full score,judging from the cases finished so far
+fn is_failed(index: usize, subtasks: &[Subtask], cases: &[CaseResult]) -> bool {
+    is_blocked(index, subtasks, cases)
+        || subtasks[index]
+            .cases
+            .iter()
+            .map(|i| &cases[*i as usize])
+            .any(|i| is_judged(i) && case_ratio(i) < 1.0)
+}
+//a subtask scores nothing if one of its dependencies failed,a subtask may only depend on earlier ones
+fn is_blocked(index: usize, subtasks: &[Subtask], cases: &[CaseResult]) -> bool {
+    match &subtasks[index].dependencies {
+        None => false,
+        Some(r) => r
+            .iter()
+            .any(|i| *i >= index || is_failed(*i, subtasks, cases)),
+    }
+}
+//whether the rest of a subtask can still change its score
+fn is_decided(index: usize, subtasks: &[Subtask], cases: &[CaseResult]) -> bool {
+    if is_blocked(index, subtasks, cases) {
+        return true;
+    }
+    let mut judged = subtasks[index]
+        .cases
+        .iter()
+        .map(|i| &cases[*i as usize])
+        .filter(|i| is_judged(i));
+    match subtasks[index].policy {
+        None | Some(SubtaskPolicy::AllOrNothing) => judged.any(|i| case_ratio(i) < 1.0),
+        Some(SubtaskPolicy::Min) => judged.any(|i| case_ratio(i) == 0.0),
+        Some(SubtaskPolicy::Sum) => false,
+    }
+}
+//case ids in the order they are judged:subtask by subtask,then the cases outside subtasks
+pub fn judge_order(problem: &Problem, subtasks: &[Subtask]) -> Vec<usize> {
+    let mut vec_order: Vec<usize> = Vec::new();
+    for i in subtasks {
+        for j in &i.cases {
+            if !vec_order.contains(&(*j as usize)) {
+                vec_order.push(*j as usize);
+            }
+        }
+    }
+    for i in 1..problem.cases.len() + 1 {
+        if !vec_order.contains(&i) {
+            vec_order.push(i);
+        }
+    }
+    vec_order
+}
+//a case is skipped once every subtask it belongs to is decided
+pub fn is_case_needed(id: usize, subtasks: &[Subtask], cases: &[CaseResult]) -> bool {
+    let mut vec_owners = (0..subtasks.len())
+        .filter(|i| subtasks[*i].cases.contains(&(id as i32)))
+        .peekable();
+    if vec_owners.peek().is_none() {
+        return true;
+    }
+    vec_owners.any(|i| !is_decided(i, subtasks, cases))
+}
+//score of the job and the result of each subtask,cases outside subtasks keep their own score
+pub fn score_job(
+    problem: &Problem,
+    subtasks: &[Subtask],
+    cases: &[CaseResult],
+) -> (f64, Vec<SubtaskResult>) {
+    let mut score = 0.0;
+    for (index, i) in problem.cases.iter().enumerate() {
+        let id = index as i32 + 1;
+        if !subtasks.iter().any(|j| j.cases.contains(&id)) {
+            score += i.score * case_ratio(&cases[id as usize]);
+        }
+    }
+    let mut vec_results = Vec::new();
+    for (index, i) in subtasks.iter().enumerate() {
+        let vec_cases: Vec<&CaseResult> = i.cases.iter().map(|j| &cases[*j as usize]).collect();
+        let mut result = EnumResult::Accepted;
+        for j in &vec_cases {
+            match j.result {
+                EnumResult::Accepted | EnumResult::Skipped => {}
+                EnumResult::Waiting | EnumResult::Running => {
+                    if let EnumResult::Accepted = result {
+                        result = EnumResult::Waiting;
+                    }
+                }
+                _ => {
+                    result = j.result.clone();
+                    break;
+                }
+            }
+        }
+        let subtask_score = if is_blocked(index, subtasks, cases) {
+            result = EnumResult::Skipped;
+            0.0
+        } else {
+            let ratios = vec_cases.iter().map(|j| case_ratio(j));
+            let ratio = match i.policy {
+                None | Some(SubtaskPolicy::AllOrNothing) => {
+                    if ratios.clone().all(|j| j == 1.0) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                Some(SubtaskPolicy::Min) => ratios.fold(1.0, f64::min),
+                Some(SubtaskPolicy::Sum) => ratios.sum::<f64>() / vec_cases.len().max(1) as f64,
+            };
+            i.score * ratio
+        };
+        score += subtask_score;
+        vec_results.push(SubtaskResult {
+            id: index as i32,
+            result,
+            score: subtask_score,
+        });
+    }
+    (score, vec_results)
+}
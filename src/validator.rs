@@ -0,0 +1,87 @@
+use super::configure::Problem;
+use super::execute::{describe_exit_status, read_truncated, set_cpu_limit, wait_with_limit};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+const DEFAULT_VALIDATOR_TIME_LIMIT: i32 = 10000000;
+const VALIDATOR_INFO_LIMIT: usize = 4096;
+//numbers the folders of validator runs
+static VALIDATE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CaseValidation {
+    pub id: i32,
+    pub input_file: String,
+    pub valid: bool,
+    //what the validator printed to stderr,or why it could not run
+    pub info: String,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValidationReport {
+    pub problem_id: i32,
+    pub valid: bool,
+    pub cases: Vec<CaseValidation>,
+}
+//run the validator on every input file of problem,None if it has no validator
+pub fn validate_problem(problem: &Problem) -> Option<ValidationReport> {
+    let validator = problem.misc.as_ref().and_then(|r| r.validator.clone())?;
+    let folder_name = format!(
+        "temp_validate{}",
+        VALIDATE_COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+    let mut cases = Vec::new();
+    for (index, case) in problem.cases.iter().enumerate() {
+        let (valid, info) = match validate_input(&validator, &case.input_file, &folder_name) {
+            Ok(r) => r,
+            Err(r) => (false, r.to_string()),
+        };
+        cases.push(CaseValidation {
+            id: index as i32 + 1,
+            input_file: case.input_file.clone(),
+            valid,
+            info,
+        });
+    }
+    let _ = std::fs::remove_dir_all(&folder_name);
+    Some(ValidationReport {
+        problem_id: problem.id,
+        valid: cases.iter().all(|i| i.valid),
+        cases,
+    })
+}
+fn validate_input(
+    validator: &[String],
+    input_file: &str,
+    folder_name: &str,
+) -> Result<(bool, String), std::io::Error> {
+    std::fs::create_dir_all(folder_name)?;
+    let err_path = format!("{}/validator.err", folder_name);
+    let mut vec_args = validator.to_vec();
+    for i in &mut vec_args {
+        if i == "%INPUT%" {
+            *i = input_file.to_string();
+        }
+    }
+    let mut command = Command::new(vec_args.remove(0));
+    command
+        .args(vec_args)
+        .stdin(Stdio::from(File::open(input_file)?))
+        .stdout(Stdio::null())
+        .stderr(Stdio::from(File::create(&err_path)?));
+    set_cpu_limit(&mut command, DEFAULT_VALIDATOR_TIME_LIMIT);
+    let mut child = command.spawn()?;
+    let status = wait_with_limit(
+        &mut child,
+        Duration::from_micros(DEFAULT_VALIDATOR_TIME_LIMIT as u64 * 2),
+        &Arc::new(AtomicBool::new(false)),
+    )?;
+    let mut info = read_truncated(&err_path, VALIDATOR_INFO_LIMIT)?;
+    if status.timed_out {
+        info.insert_str(0, "Validator time limit exceeded\n");
+    } else if !status.status.success() && info.is_empty() {
+        info = describe_exit_status(&status.status);
+    }
+    Ok((!status.timed_out && status.status.success(), info))
+}
@@ -0,0 +1,95 @@
+use super::configure::{Language, Problem, SolutionCheck};
+use super::enumresult::EnumResult;
+use super::execute::{execute_input_inner, new_message, CancelFlag, CaseResult, Submit};
+use actix_web::web;
+use chrono::prelude::*;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicI32, Ordering};
+//verification jobs take negative ids,so they never match a row of the task table
+static VERIFY_COUNTER: AtomicI32 = AtomicI32::new(-1);
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SolutionVerification {
+    pub source_file: String,
+    pub language: String,
+    pub expected: EnumResult,
+    pub result: EnumResult,
+    pub score: f64,
+    pub passed: bool,
+    pub cases: Vec<CaseResult>,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VerificationReport {
+    pub problem_id: i32,
+    pub passed: bool,
+    pub solutions: Vec<SolutionVerification>,
+}
+fn verify_solution(
+    solution: &SolutionCheck,
+    problem: &Problem,
+    languages: &[Language],
+    pool: &web::Data<Pool<SqliteConnectionManager>>,
+) -> Result<(EnumResult, f64, Vec<CaseResult>), Box<dyn std::error::Error>> {
+    let source_code = std::fs::read_to_string(&solution.source_file)?;
+    let language = languages
+        .iter()
+        .find(|i| i.name == solution.language)
+        .ok_or(format!("language {} not found", solution.language))?;
+    let id = VERIFY_COUNTER.fetch_sub(1, Ordering::SeqCst);
+    let message = new_message(
+        id,
+        Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        Submit {
+            source_code,
+            language: solution.language.clone(),
+            user_id: 0,
+            contest_id: 0,
+            problem_id: problem.id,
+        },
+        problem,
+    );
+    let message = execute_input_inner(
+        message,
+        pool.clone(),
+        problem.clone(),
+        language.clone(),
+        &CancelFlag::default(),
+    )
+    .inspect_err(|_| {
+        let _ = std::fs::remove_dir_all(format!("temp{}", id));
+    })?;
+    Ok((message.result, message.score, message.cases))
+}
+//judge the solutions listed by problem one by one,None if it lists none
+pub fn verify_problem(
+    problem: &Problem,
+    languages: &[Language],
+    pool: &web::Data<Pool<SqliteConnectionManager>>,
+) -> Option<VerificationReport> {
+    let list = problem.misc.as_ref().and_then(|r| r.solutions.clone())?;
+    let mut solutions = Vec::new();
+    for solution in &list {
+        let (result, score, cases) = match verify_solution(solution, problem, languages, pool) {
+            Ok(r) => r,
+            Err(r) => {
+                log::error!("Cannot verify {}: {}", solution.source_file, r);
+                (EnumResult::SystemError, 0.0, Vec::new())
+            }
+        };
+        solutions.push(SolutionVerification {
+            source_file: solution.source_file.clone(),
+            language: solution.language.clone(),
+            expected: solution.expected.clone(),
+            passed: result.to_string() == solution.expected.to_string(),
+            result,
+            score,
+            cases,
+        });
+    }
+    Some(VerificationReport {
+        problem_id: problem.id,
+        passed: solutions.iter().all(|i| i.passed),
+        solutions,
+    })
+}
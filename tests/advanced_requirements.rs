@@ -1,6 +1,8 @@
 mod common;
 use common::TestCase;
 use std::collections::BTreeMap;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
 
 #[test]
 fn test_adv_01_10_pts_contest_support() {
@@ -48,7 +50,8 @@ fn test_adv_04_10_pts_resource_limit() {
         "case adv_04_01_report_memory_usage incorrect: memory usage should be greater than 0"
     );
 
-    // limit the memory usage to 10MB, allocate 40MB memory in submission, then check that the job result is MLE
+    // limit the memory usage to 10MB, allocate 40MB memory in submission, then check that the job result is MLE,
+    // also for an allocation of 1GB at once, while a small program is still accepted
     TestCase::read("adv_04_02_limit_memory_usage").run();
 }
 
@@ -126,3 +129,200 @@ fn test_adv_07_10_pts_dynamic_ranking() {
         );
     }
 }
+
+#[test]
+fn test_adv_08_output_limit() {
+    // limit the output to 1KB, print forever in submission, then check that the job result is OLE,
+    // also for Python which ignores SIGXFSZ, while writing to stderr counts against no limit
+    TestCase::read("adv_08_output_limit").run();
+}
+
+#[test]
+fn test_adv_09_interactive() {
+    // check that interactive problems are supported
+    // use a Python interactor that answers guesses of a secret number
+    // and that its verdict wins over a broken pipe of a submission that keeps writing
+    TestCase::read("adv_09_interactive").run();
+}
+
+#[test]
+fn test_adv_10_judge_workers() {
+    // run a single judge worker, then check that the second job waits in the queue
+    // until the first one is finished
+    TestCase::read("adv_10_judge_workers").run();
+}
+
+#[test]
+fn test_adv_11_recover_jobs() {
+    // restart the server while a job is running and another is queueing,
+    // then check that both are judged after the restart
+    TestCase::read("adv_11_recover_jobs").run();
+}
+
+#[test]
+fn test_adv_12_cancel_job() {
+    // cancel a queueing job and a running job, then check that the next job
+    // is judged without waiting for the canceled one to time out
+    TestCase::read("adv_12_cancel_job").run();
+}
+
+#[test]
+fn test_adv_13_bulk_rejudge() {
+    // rejudge all wrong answers of a problem at once, then check the summary
+    // and that the rejudged jobs are finished again
+    TestCase::read("adv_13_bulk_rejudge").run();
+}
+
+#[test]
+fn test_adv_14_subtasks() {
+    // check the all_or_nothing, min and sum policies of subtasks,
+    // and that a subtask depending on a failed one is skipped
+    TestCase::read("adv_14_subtasks").run();
+}
+
+#[test]
+fn test_adv_15_spj_partial_score() {
+    // the special judge gives half of the score of each case to a non-optimal answer
+    TestCase::read("adv_15_spj_partial_score").run();
+}
+
+#[test]
+fn test_adv_16_testlib_checker() {
+    // use a checker following the testlib protocol, then check the mapping
+    // of its exit codes to results
+    TestCase::read("adv_16_testlib_checker").run();
+}
+
+#[test]
+fn test_adv_17_spj_limits() {
+    // use special judges that loop forever or use too much memory,
+    // then check that the cases get SPJ Error instead of hanging the judge
+    TestCase::read("adv_17_spj_limits").run();
+}
+
+#[test]
+fn test_adv_18_checker_source() {
+    // declare the special judge as a Rust source file, which the server compiles at startup
+    TestCase::read("adv_18_checker_source").run();
+}
+
+#[test]
+fn test_adv_19_comparators() {
+    // check the built-in comparators: tokens, float with eps, case-insensitive,
+    // unordered lines and yes/no
+    TestCase::read("adv_19_comparators").run();
+}
+
+#[test]
+fn test_adv_20_presentation_error() {
+    // enable presentation_error, then check that outputs differing only in whitespace
+    // get Presentation Error while other mismatches stay Wrong Answer
+    TestCase::read("adv_20_presentation_error").run();
+}
+
+#[test]
+fn test_adv_21_wrong_answer_diagnostics() {
+    // check that wrong answers report where the output differs from the answer,
+    // in the job itself or only from /internal/jobs/{id} depending on the problem
+    TestCase::read("adv_21_wrong_answer_diagnostics").run();
+}
+
+#[test]
+fn test_adv_22_runtime_error_details() {
+    // end submissions with an exit code or by signals, then check the info of
+    // the runtime errors, that a SIGKILL stays a runtime error under a memory limit
+    // and that SIGXCPU gives Time Limit Exceeded
+    TestCase::read("adv_22_runtime_error_details").run();
+}
+
+#[test]
+fn test_adv_23_custom_run() {
+    // run programs on custom input, then check their output, verdicts and
+    // that the first job submitted afterwards still gets id 0
+    TestCase::read("adv_23_custom_run").run();
+}
+
+#[test]
+fn test_adv_24_data_validators() {
+    // run the validators of two problems on demand, then check the report
+    // of the invalid input file
+    TestCase::read("adv_24_01_validate_on_demand").run();
+
+    // validate the data at startup, then check that only the problem with
+    // valid data is served
+    TestCase::read("adv_24_02_validate_at_startup").run();
+}
+
+#[test]
+fn test_adv_25_generated_data() {
+    // generate the data of two cases with a Python generator and reference solution,
+    // then judge a correct and a wrong submission against them
+    TestCase::read("adv_25_generated_data").run();
+}
+
+#[test]
+fn test_adv_26_verify_solutions() {
+    // verify the listed solutions of two problems, then check that a slow solution
+    // that the data does not catch fails the verification, and that no job is created
+    TestCase::read("adv_26_verify_solutions").run();
+}
+
+#[test]
+fn test_adv_27_compile_cache() {
+    // use a compiler that fails when it runs twice, then check that a duplicate
+    // submission and a rejudge reuse the first compilation
+    TestCase::read("adv_27_compile_cache").run();
+}
+
+#[test]
+fn test_adv_28_language_pipelines() {
+    // run Python without a compile step, inside and outside the sandbox, and with
+    // two compile steps that check the syntax before running
+    TestCase::read("adv_28_language_pipelines").run();
+}
+
+#[test]
+fn test_adv_29_sandbox() {
+    // run Python in the default sandbox, then check that it can neither uncover the
+    // judge's directory to read or delete file.db nor fork without bound, and that links
+    // it leaves behind are not followed when the judge writes the output of the next case;
+    // as root, processes of nobody outside the sandbox must not count against a job's processes
+    let others: Vec<_> = if unsafe { libc::getuid() } == 0 {
+        (0..64)
+            .map(|_| Command::new("sleep").arg("60").uid(65534).spawn().unwrap())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    TestCase::read("adv_29_sandbox").run();
+    for mut i in others {
+        let _ = i.kill();
+        let _ = i.wait();
+    }
+    assert!(std::path::Path::new("file.db").exists());
+}
+
+#[test]
+fn test_adv_30_idleness_limit() {
+    // a sleeping program and one blocked on the interactor get Idleness Limit Exceeded,
+    // a busy loop gets Time Limit Exceeded, and the time of each case is cpu time
+    let results = TestCase::read("adv_30_idleness_limit").run();
+    let time = |i: usize| results[i]["cases"][1]["time"].as_u64().unwrap();
+    assert!(
+        time(0) < 500000,
+        "a sleeping program should use little cpu time"
+    );
+    assert!(
+        time(1) < 500000,
+        "a blocked program should use little cpu time"
+    );
+    assert!(time(2) > 500000, "a busy loop should use up its cpu time");
+}
+
+#[test]
+fn test_adv_31_compile_info() {
+    // use a compiler that reports errors and warnings on stderr, then check that they
+    // reach the info of the compilation case, cut at compile_info_limit, and that a
+    // compiler running past compile_time_limit fails the compilation
+    TestCase::read("adv_31_compile_info").run();
+}
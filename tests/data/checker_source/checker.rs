@@ -0,0 +1,15 @@
+// compare the output with the answer number by number
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let output = std::fs::read_to_string(&args[1]).unwrap();
+    let answer = std::fs::read_to_string(&args[2]).unwrap();
+    let output: Vec<&str> = output.split_whitespace().collect();
+    let answer: Vec<&str> = answer.split_whitespace().collect();
+    if output == answer {
+        println!("Accepted");
+        println!("{} numbers match", answer.len());
+    } else {
+        println!("Wrong Answer");
+        println!("numbers differ");
+    }
+}
@@ -0,0 +1,18 @@
+#!/usr/bin/env python3
+import os
+import sys
+
+source = sys.argv[1]
+output = sys.argv[2]
+
+# the server removes temp* folders at startup, so this only remembers the current run
+if os.path.isdir('temp_compiled_once'):
+    print('compiled twice')
+    sys.exit(1)
+os.makedirs('temp_compiled_once')
+
+with open(output, 'w') as f:
+    f.write('#!/usr/bin/env python3\n')
+    f.write(open(source).read())
+os.chmod(output, 0o755)
+print('compiled once')
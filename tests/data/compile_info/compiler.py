@@ -0,0 +1,27 @@
+#!/usr/bin/env python3
+import os
+import sys
+import time
+
+source = sys.argv[1]
+output = sys.argv[2]
+
+# lines starting with "#! " are diagnostics for stderr, "#! error" fails the
+# compilation and "#! sleep" keeps the compiler busy
+code = open(source).read()
+failed = False
+for line in code.splitlines():
+    if not line.startswith('#! '):
+        continue
+    message = line[3:]
+    if message == 'sleep':
+        time.sleep(10)
+    sys.stderr.write(message + '\n')
+    failed = failed or message.startswith('error')
+if failed:
+    sys.exit(1)
+
+with open(output, 'w') as f:
+    f.write('#!/usr/bin/env python3\n')
+    f.write(code)
+os.chmod(output, 0o755)
@@ -0,0 +1,9 @@
+#!/usr/bin/env python3
+import random
+import sys
+
+count = int(sys.argv[1])
+random.seed(int(sys.argv[2]))
+
+for _ in range(count):
+    print(random.randint(1, 10000))
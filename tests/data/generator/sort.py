@@ -0,0 +1,5 @@
+#!/usr/bin/env python3
+import sys
+
+for number in sorted(int(i) for i in sys.stdin.read().split()):
+    print(number)
@@ -0,0 +1,17 @@
+#!/usr/bin/env python3
+import sys
+
+secret = int(open(sys.argv[1], 'r').read().strip())
+
+for _ in range(20):
+    line = sys.stdin.readline()
+    if not line:
+        break
+    guess = int(line.strip())
+    if guess == secret:
+        print('=', flush=True)
+        sys.stderr.write('Accepted\nGuessed the number\n')
+        sys.exit(0)
+    print('<' if guess < secret else '>', flush=True)
+
+sys.stderr.write('Wrong Answer\nFailed to guess the number\n')
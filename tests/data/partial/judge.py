@@ -0,0 +1,19 @@
+#!/usr/bin/env python3
+import sys
+
+output = sys.argv[1]
+answer = sys.argv[2]
+
+output_number = int(open(output, 'r').read().strip())
+answer_number = int(open(answer, 'r').read().strip())
+
+if output_number == answer_number:
+    print('Accepted')
+    print('Optimal')
+elif 0 < output_number < answer_number:
+    print('Partially Accepted')
+    print('Not optimal')
+    print(output_number / answer_number)
+else:
+    print('Wrong Answer')
+    print('Out of range')
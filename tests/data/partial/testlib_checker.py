@@ -0,0 +1,20 @@
+#!/usr/bin/env python3
+# behaves like a testlib checker: checker <input> <output> <answer>
+import sys
+
+input_number = int(open(sys.argv[1], 'r').read().strip())
+output = open(sys.argv[2], 'r').read().strip()
+answer_number = int(open(sys.argv[3], 'r').read().strip())
+
+if not output.lstrip('-').isdigit():
+    sys.stderr.write('wrong output format Expected integer, but "%s" found\n' % output)
+    sys.exit(2)
+output_number = int(output)
+if output_number == answer_number:
+    sys.stderr.write('ok answer is %d\n' % input_number)
+    sys.exit(0)
+if 0 < output_number < answer_number:
+    sys.stderr.write('points %s not optimal\n' % (output_number / answer_number))
+    sys.exit(7)
+sys.stderr.write('wrong answer %d is out of range\n' % output_number)
+sys.exit(1)
@@ -0,0 +1,4 @@
+#!/usr/bin/env python3
+# a broken special judge that never finishes
+while True:
+    pass
@@ -0,0 +1,5 @@
+#!/usr/bin/env python3
+# a broken special judge that grows to about 256MB
+buffer = [bytearray(1024 * 1024) for _ in range(256)]
+print('Accepted')
+print('Used %d bytes' % sum(len(i) for i in buffer))
@@ -0,0 +1,12 @@
+#!/usr/bin/env python3
+import sys
+
+tokens = sys.stdin.read().split()
+
+if len(tokens) != 2:
+    print('expected 2 numbers, found {}'.format(len(tokens)), file=sys.stderr)
+    sys.exit(1)
+for token in tokens:
+    if not token.isdigit() or not 0 <= int(token) <= 10000:
+        print('{} is not an integer in [0, 10000]'.format(token), file=sys.stderr)
+        sys.exit(1)
@@ -0,0 +1,15 @@
+use std::io::*;
+
+fn main() {
+    let mut s = String::new();
+    stdin().read_to_string(&mut s).unwrap();
+    let mut sum: i64 = 0;
+    for i in s.split_whitespace() {
+        let n = i.parse::<i64>().unwrap();
+        //count up one at a time,which is still fast for the small numbers of the data
+        for _ in 0..n {
+            sum = std::hint::black_box(sum + 1);
+        }
+    }
+    println!("{}", sum);
+}
@@ -0,0 +1,8 @@
+use std::io::*;
+
+fn main() {
+    let mut s = String::new();
+    stdin().read_to_string(&mut s).unwrap();
+    let product: i64 = s.split_whitespace().map(|i| i.parse::<i64>().unwrap()).product();
+    println!("{}", product);
+}